@@ -0,0 +1,462 @@
+use std::{
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::bot::{Bot, BotSocket};
+use crate::cli::{self, Command, Options};
+use crate::daily::{daily_config, Date};
+use crate::log::{info, warning};
+use crate::log_panel::LogPanel;
+use crate::menu::{MenuAction, PauseMenu};
+use crate::replay::{Recorder, Replay};
+use crate::run::Run;
+use crate::run_config::RunConfig;
+use crate::scores::HighScores;
+use crate::settings::Settings;
+use crate::solution::SolutionRecorder;
+use crate::terminal::{init_terminal, restore_terminal, Signals, Term};
+use crate::title::{TitleAction, TitleScreen};
+use crate::{run_code, save, terminal, verify};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    terminal::Frame,
+};
+
+/// Area of the games when simulating without a terminal
+const HEADLESS_AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 160,
+    height: 48,
+};
+
+/// Runs the command line program
+pub fn main() -> io::Result<()> {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(options)) => *options,
+        Ok(Command::Verify(options)) => {
+            let survived = verify::run(&options);
+            std::process::exit(if survived { 0 } else { 1 });
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    crate::log::init(options.log_file.as_deref())?;
+
+    if options.headless {
+        run_headless(&options)
+    } else if options.bot {
+        run_bot(&options)
+    } else {
+        App::run(options)
+    }
+}
+
+/// Simulates the whole replay as fast as possible and prints the result
+fn run_headless(options: &Options) -> io::Result<()> {
+    let Some(path) = &options.replay else {
+        return Ok(());
+    };
+
+    let settings = Settings::new();
+    let mut run = Run::from_replay(Replay::load(path)?);
+
+    if let Some(path) = &options.record {
+        run.record(Recorder::create(path, run.config())?);
+    }
+    if let Some(path) = &options.export_solution {
+        run.record_solution(SolutionRecorder::create(path, run.config())?);
+    }
+
+    while run.is_replaying() {
+        run.update(HEADLESS_AREA, &settings);
+        crate::log::next_tick();
+    }
+
+    println!("ticks: {}", run.ticks());
+    println!("score: {}", run.score());
+
+    if let Some(tick) = run.diverged() {
+        println!("diverged: tick {tick}");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Lets a bot play the run through stdin and stdout, until it closes its output
+fn run_bot(options: &Options) -> io::Result<()> {
+    let settings = Settings::new();
+    let mut run = if options.resume {
+        load_save(options)?
+    } else {
+        // Without `--games` the rest of the options still apply, to every game
+        Run::new(chosen_config(options).unwrap_or_else(|| options.config()))
+    };
+
+    if let Some(path) = &options.record {
+        run.record(Recorder::create(path, run.config())?);
+    }
+    if let Some(path) = &options.export_solution {
+        run.record_solution(SolutionRecorder::create(path, run.config())?);
+    }
+
+    let mut bot = Bot::new(io::stdin().lock(), io::stdout().lock());
+    loop {
+        bot.send(&run)?;
+        let Some(keys) = bot.receive()? else {
+            return Ok(());
+        };
+
+        run.hold_keys(keys);
+        run.update(HEADLESS_AREA, &settings);
+        crate::log::next_tick();
+    }
+}
+
+/// Configuration of the run chosen with the options, if any
+fn chosen_config(options: &Options) -> Option<RunConfig> {
+    if let Some(config) = &options.code {
+        return Some(config.clone());
+    }
+    if options.daily {
+        return Some(daily_config(Date::today()));
+    }
+
+    options.games.as_ref()?;
+    Some(options.config())
+}
+
+/// Resumes the run of the save file
+fn load_save(options: &Options) -> io::Result<Run> {
+    match options.save.clone().or_else(save::default_path) {
+        Some(path) => save::load(&path),
+        None => {
+            let message = "there is no save file, use '--save <PATH>'";
+            Err(io::Error::new(io::ErrorKind::NotFound, message))
+        }
+    }
+}
+
+struct App {
+    /// The title screen is shown while there is no run
+    run: Option<Run>,
+    title: TitleScreen,
+    scores: HighScores,
+    log_panel: LogPanel,
+    menu: PauseMenu,
+    settings: Settings,
+    /// The score of the current run goes to the high scores, not for replayed or resumed runs
+    scored: bool,
+    /// Replay file where every started run is recorded
+    record: Option<PathBuf>,
+    /// File where the run is saved and resumed from
+    save: Option<PathBuf>,
+    /// File where the solution of every started run is written
+    export_solution: Option<PathBuf>,
+    /// Socket where a bot can connect to play instead of the keyboard
+    bot: Option<BotSocket>,
+    frame_time: Duration,
+    tick_rate: Duration,
+    close: bool,
+    suspend: bool,
+}
+
+impl App {
+    fn new(options: &Options) -> Self {
+        Self {
+            run: None,
+            title: TitleScreen::new(options.config()),
+            scores: HighScores::load(),
+            log_panel: LogPanel::new(),
+            menu: PauseMenu::new(options.tick_rate),
+            settings: Settings::new(),
+            scored: false,
+            record: options.record.clone(),
+            save: options.save.clone().or_else(save::default_path),
+            export_solution: options.export_solution.clone(),
+            bot: None,
+            frame_time: options.frame_time,
+            tick_rate: options.tick_rate,
+            close: false,
+            suspend: false,
+        }
+    }
+
+    pub fn run(options: Options) -> io::Result<()> {
+        // Load the replay before touching the terminal, so errors can be printed
+        let replay = match &options.replay {
+            Some(path) => Some(Replay::load(path)?),
+            None => None,
+        };
+        let resumed = if options.resume {
+            Some(load_save(&options)?)
+        } else {
+            None
+        };
+        // Half a tick is left to simulate and draw after waiting for the bot
+        let bot = match &options.bot_socket {
+            Some(path) => Some(BotSocket::bind(path, options.tick_rate / 2)?),
+            None => None,
+        };
+
+        terminal::install_panic_hook();
+        let signals = Signals::register()?;
+
+        let mut terminal = init_terminal()?;
+        let mut app = Self::new(&options);
+        app.bot = bot;
+
+        if let Some(replay) = replay {
+            app.start_run(Run::from_replay(replay));
+        } else if let Some(run) = resumed {
+            app.start_run(run);
+        } else if let Some(config) = chosen_config(&options) {
+            app.start_run(Run::new(config));
+        }
+
+        let result = app.main_loop(&mut terminal, &signals);
+        app.end_run();
+
+        restore_terminal()?;
+        result
+    }
+
+    fn main_loop(&mut self, terminal: &mut Term, signals: &Signals) -> io::Result<()> {
+        // if less than `tick_margin` time is left, do not sleep, insted do a busy wait.
+        let tick_margin = Duration::from_millis(5);
+        let mut last_tick = Instant::now();
+        let mut last_frame = Instant::now();
+
+        while !self.close && !signals.terminate_requested() {
+            let mut resumed = signals.handle_pending(terminal)?;
+
+            if self.suspend {
+                self.suspend = false;
+                terminal::suspend(terminal)?;
+                resumed = true;
+            }
+
+            // Do not try to catch up the time spent suspended
+            if resumed {
+                last_tick = Instant::now();
+                last_frame = Instant::now();
+            }
+
+            let next_tick = self.tick_rate.saturating_sub(last_tick.elapsed());
+            let next_frame = self.frame_time.saturating_sub(last_frame.elapsed());
+            let timeout = next_tick.min(next_frame).saturating_sub(tick_margin);
+
+            if event::poll(timeout)? {
+                match event::read()? {
+                    Event::Key(key) => self.handle_key(key),
+                    Event::FocusLost if self.settings.pause_on_focus_lost => self.menu.open(),
+                    _ => {}
+                }
+            }
+
+            if last_tick.elapsed() >= self.tick_rate {
+                last_tick += self.tick_rate;
+                self.update(terminal.size()?);
+            }
+
+            if last_frame.elapsed() >= self.frame_time {
+                last_frame += self.frame_time;
+                let _ = terminal.draw(|frame| self.ui(frame));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key_event.code {
+            KeyCode::Char('c') if ctrl => self.close = true,
+            KeyCode::Char('z') if ctrl => self.suspend = true,
+            KeyCode::F(3) if key_event.kind == KeyEventKind::Press => {
+                self.settings.show_highway = !self.settings.show_highway;
+            }
+            KeyCode::F(4) if key_event.kind == KeyEventKind::Press => {
+                self.settings.show_ghost = !self.settings.show_ghost;
+            }
+            _ => {}
+        }
+
+        if self.run.is_none() {
+            match self.title.handle_key(key_event) {
+                Some(TitleAction::Start(config)) => self.start_run(Run::new(config)),
+                Some(TitleAction::Resume) => self.resume_run(),
+                Some(TitleAction::Quit) => self.close = true,
+                None => {}
+            }
+            return;
+        }
+
+        // The focused log panel gets the keys first, so Esc and 'q' do not open the menu
+        if !self.menu.is_open()
+            && self.log_panel.is_focused()
+            && self.log_panel.handle_key(key_event)
+        {
+            return;
+        }
+
+        match self.menu.handle_key(key_event, &mut self.settings) {
+            Some(MenuAction::Save) => self.save_run(),
+            Some(MenuAction::Quit) => {
+                self.end_run();
+            }
+            Some(MenuAction::Restart) => {
+                if let Some(config) = self.end_run() {
+                    self.start_run(Run::new(config));
+                }
+            }
+            Some(MenuAction::None) => {}
+            None => {
+                if self.log_panel.handle_key(key_event) {
+                    return;
+                }
+
+                let Some(run) = &mut self.run else {
+                    return;
+                };
+
+                match key_event.code {
+                    KeyCode::Char(digit @ '1'..='9') if key_event.kind == KeyEventKind::Press => {
+                        run.toggle_zoom(digit as usize - '1' as usize);
+                    }
+                    // The bot plays instead of the keyboard
+                    _ if self.bot.as_ref().is_some_and(BotSocket::is_connected) => {}
+                    _ => run.handle_key_event(key_event),
+                }
+            }
+        }
+    }
+
+    fn start_run(&mut self, mut run: Run) {
+        if let Some(path) = &self.record {
+            if run.ticks() > 0 {
+                warning!("Not recording the resumed run, replays start from the first tick");
+            } else {
+                match Recorder::create(path, run.config()) {
+                    Ok(recorder) => run.record(recorder),
+                    Err(error) => warning!("Can not record to {}: {error}", path.display()),
+                }
+            }
+        }
+        if let Some(path) = &self.export_solution {
+            match SolutionRecorder::create(path, run.config()) {
+                Ok(recorder) => run.record_solution(recorder),
+                Err(error) => {
+                    warning!("Can not export the solution to {}: {error}", path.display())
+                }
+            }
+        }
+
+        self.scored = !run.is_replaying() && run.ticks() == 0;
+        self.run = Some(run);
+    }
+
+    /// Saves the current run and quits it, without recording its score
+    fn save_run(&mut self) {
+        let (Some(run), Some(path)) = (&self.run, &self.save) else {
+            warning!("There is no file to save the run to");
+            return;
+        };
+
+        match save::save(run, path) {
+            Ok(()) => {
+                info!("Saved the run to {}", path.display());
+                self.run = None;
+            }
+            Err(error) => warning!("Can not save the run to {}: {error}", path.display()),
+        }
+    }
+
+    fn resume_run(&mut self) {
+        let Some(path) = &self.save else {
+            self.title.resume_failed();
+            return;
+        };
+
+        match save::load(path) {
+            Ok(run) => self.start_run(run),
+            Err(error) => {
+                warning!("Can not resume the run of {}: {error}", path.display());
+                self.title.resume_failed();
+            }
+        }
+    }
+
+    /// Records the score of the current run and returns its configuration
+    fn end_run(&mut self) -> Option<RunConfig> {
+        let run = self.run.take()?;
+        if self.scored {
+            self.scores.add(run.config(), run.score());
+        }
+        Some(run.config().clone())
+    }
+
+    /// Advances the simulation a tick, `area` is the size of the terminal
+    fn update(&mut self, area: Rect) {
+        if self.menu.is_paused() {
+            self.menu.update();
+            return;
+        }
+
+        if let Some(run) = &mut self.run {
+            if let Some(keys) = self.bot.as_mut().and_then(|bot| bot.exchange(run)) {
+                run.hold_keys(keys);
+            }
+
+            let [_, games_area] = Self::layout(run, &self.log_panel, area);
+            run.update(games_area, &self.settings);
+            crate::log::next_tick();
+        }
+    }
+
+    /// Splits the terminal area into the log column and the games area
+    fn layout(run: &Run, log_panel: &LogPanel, area: Rect) -> [Rect; 2] {
+        use Constraint::*;
+
+        // A zoomed game uses the whole terminal
+        let log_width = if run.is_zoomed() {
+            0
+        } else {
+            log_panel.width()
+        };
+
+        Layout::horizontal([Length(log_width), Fill(1)]).areas(area)
+    }
+
+    fn ui(&mut self, frame: &mut Frame) {
+        let Some(run) = &mut self.run else {
+            self.title.render(frame, &self.scores);
+            return;
+        };
+
+        let [log_column, games_area] = Self::layout(run, &self.log_panel, frame.size());
+
+        if log_column.width != 0 {
+            frame.render_widget(
+                self.log_panel.widget(log_column.as_size().into()),
+                log_column,
+            );
+        }
+
+        run.render(frame, games_area, &self.settings, self.tick_rate);
+
+        let code = run_code::encode(run.config()).unwrap_or_default();
+        self.menu.render(frame, &self.settings, &code);
+    }
+}
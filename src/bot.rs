@@ -0,0 +1,336 @@
+use crate::input::Key;
+use crate::log::{debug, info, warning};
+use crate::run::Run;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::{
+    fs,
+    io::BufReader,
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+/// Program that plays instead of the keyboard, one line of text per message
+///
+/// Before each tick the bot is sent a JSON line with the state of the run:
+///
+/// ```text
+/// {"id":12,"tick":12,"score":3,"games":[{"game":"trex","colliding":false,"state":[0.5,0,0,1]}]}
+/// ```
+///
+/// The games are the ones simulated in the previous tick, so the first line has none. The id
+/// counts the lines sent to the bot, unlike the tick it never goes back when the run rewinds.
+/// The bot replies with a line with the id of the state and the keys to hold during the tick,
+/// as a bitmask where each bit is a key, from the lowest bit: up, down, left, right and space.
+/// For example `12 17` is up and space. Replies to older states, which came too late for their
+/// tick, are skipped.
+pub struct Bot<R, W> {
+    reader: R,
+    writer: W,
+    /// States sent so far, the id of the next one
+    sent: usize,
+    /// Part of the reply read so far
+    reply: Vec<u8>,
+}
+
+impl<R: BufRead, W: Write> Bot<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            sent: 0,
+            reply: Vec::new(),
+        }
+    }
+
+    /// Sends the state of `run`
+    pub fn send(&mut self, run: &Run) -> io::Result<()> {
+        writeln!(self.writer, "{}", describe(self.sent, run))?;
+        self.sent += 1;
+        self.writer.flush()
+    }
+
+    /// Waits for the keys of the reply to the last state sent, returns `None` once the bot has
+    /// closed its output
+    ///
+    /// If the reader times out, the part of the reply read so far is kept for the next call.
+    pub fn receive(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            self.reader.read_until(b'\n', &mut self.reply)?;
+            if self.reply.last() != Some(&b'\n') {
+                return Ok(None);
+            }
+
+            let reply = String::from_utf8_lossy(&self.reply).trim().to_string();
+            self.reply.clear();
+
+            let Some((id, mask)) = parse_reply(&reply) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid reply '{reply}' sent by the bot"),
+                ));
+            };
+
+            if id + 1 == self.sent {
+                return Ok(Some(mask));
+            } else if id + 1 < self.sent {
+                debug!("Skipped the late reply of the bot to state {id}");
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("reply to state {id} sent by the bot, which has not been sent"),
+                ));
+            }
+        }
+    }
+}
+
+/// Id and keys of a reply
+fn parse_reply(reply: &str) -> Option<(usize, u8)> {
+    let (id, mask) = reply.split_once(' ')?;
+    let mask: u8 = mask.trim().parse().ok()?;
+
+    (mask >> Key::ALL.len() == 0).then_some((id.parse().ok()?, mask))
+}
+
+/// Line with the state of `run` that is sent to the bot as state `id`
+fn describe(id: usize, run: &Run) -> String {
+    let mut line = format!(
+        "{{\"id\":{id},\"tick\":{},\"score\":{},\"games\":[",
+        run.ticks(),
+        run.score()
+    );
+
+    for (i, (game, state)) in run.game_states().into_iter().enumerate() {
+        let separator = if i == 0 { "" } else { "," };
+        let state: Vec<_> = state.iter().map(f32::to_string).collect();
+        let _ = write!(
+            line,
+            "{separator}{{\"game\":\"{}\",\"colliding\":{},\"state\":[{}]}}",
+            game.id(),
+            run.is_colliding(game),
+            state.join(",")
+        );
+    }
+
+    line.push_str("]}");
+    line
+}
+
+/// Unix socket where a bot can connect to play the run shown in the terminal
+///
+/// Only one bot plays at a time, the next one can connect once it disconnects.
+#[cfg(unix)]
+pub struct BotSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    /// How long to wait for each reply before simulating the tick with the keys held before,
+    /// and for each state to be sent before disconnecting the bot
+    reply_timeout: Duration,
+    bot: Option<Bot<BufReader<UnixStream>, UnixStream>>,
+}
+
+#[cfg(unix)]
+impl BotSocket {
+    /// Listens at `path`, replacing the socket left there by a previous run
+    pub fn bind(path: &Path, reply_timeout: Duration) -> io::Result<Self> {
+        if fs::metadata(path).is_ok_and(|metadata| is_socket(&metadata)) {
+            fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            reply_timeout,
+            bot: None,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.bot.is_some()
+    }
+
+    /// Sends the state of `run` to the connected bot and returns the keys of its reply,
+    /// `None` if there is no bot or it did not reply in time
+    ///
+    /// When the bot disconnects, or stops reading the states, the keys it was holding are
+    /// released.
+    pub fn exchange(&mut self, run: &Run) -> Option<u8> {
+        if self.bot.is_none() {
+            self.accept();
+        }
+        let bot = self.bot.as_mut()?;
+
+        let reply = match bot.send(run) {
+            Ok(()) => match bot.receive() {
+                Err(error) if is_timeout(&error) => return None,
+                reply => reply,
+            },
+            // The states would keep blocking until the bot reads them
+            Err(error) if is_timeout(&error) => {
+                Err(io::Error::other("the bot is not reading the states"))
+            }
+            Err(error) => Err(error),
+        };
+
+        match reply {
+            Ok(Some(keys)) => return Some(keys),
+            Ok(None) => info!("The bot has disconnected"),
+            Err(error) if is_disconnection(&error) => info!("The bot has disconnected"),
+            Err(error) => warning!("Disconnected the bot: {error}"),
+        }
+
+        self.bot = None;
+        Some(0)
+    }
+
+    fn accept(&mut self) {
+        let stream = match self.listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => return,
+            Err(error) => {
+                warning!("Can not accept the bot: {error}");
+                return;
+            }
+        };
+
+        let connect = |stream: UnixStream| {
+            stream.set_nonblocking(false)?;
+            stream.set_read_timeout(Some(self.reply_timeout))?;
+            stream.set_write_timeout(Some(self.reply_timeout))?;
+            Ok::<_, io::Error>(Bot::new(BufReader::new(stream.try_clone()?), stream))
+        };
+
+        match connect(stream) {
+            Ok(bot) => {
+                info!("A bot has connected to {}", self.path.display());
+                self.bot = Some(bot);
+            }
+            Err(error) => warning!("Can not accept the bot: {error}"),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for BotSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Unix sockets are not available on this platform
+#[cfg(not(unix))]
+pub struct BotSocket;
+
+#[cfg(not(unix))]
+impl BotSocket {
+    pub fn bind(_path: &Path, _reply_timeout: Duration) -> io::Result<Self> {
+        let message = "bot sockets are only supported on Unix systems";
+        Err(io::Error::new(io::ErrorKind::Unsupported, message))
+    }
+
+    pub fn is_connected(&self) -> bool {
+        false
+    }
+
+    pub fn exchange(&mut self, _run: &Run) -> Option<u8> {
+        None
+    }
+}
+
+#[cfg(unix)]
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(unix)]
+fn is_disconnection(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset
+    )
+}
+
+#[cfg(unix)]
+fn is_socket(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_socket()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::GameKind;
+    use crate::run_config::RunConfig;
+    use crate::settings::Settings;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn bot_plays_the_run() {
+        let mut config = RunConfig::new();
+        config.games = vec![GameKind::TRex];
+        let mut run = Run::new(config);
+
+        // Jumps every 20 ticks, with a late reply to the first state before the 50th, then
+        // sends an invalid reply
+        let replies: String = (0..100)
+            .map(|id| match id {
+                50 => "0 16\n50 0\n".to_string(),
+                id if id % 20 == 0 => format!("{id} 16\n"),
+                id => format!("{id} 0\n"),
+            })
+            .chain(["100 32\n".to_string()])
+            .collect();
+        let mut output = Vec::new();
+        let mut bot = Bot::new(replies.as_bytes(), &mut output);
+
+        for _ in 0..100 {
+            bot.send(&run).unwrap();
+            let keys = bot.receive().unwrap().expect("a reply for each tick");
+            run.hold_keys(keys);
+            run.update(Rect::new(0, 0, 160, 48), &Settings::new());
+        }
+        bot.send(&run).unwrap();
+        assert!(bot.receive().is_err());
+        assert_eq!(parse_reply("3 17"), Some((3, 17)));
+        assert_eq!(parse_reply("17"), None);
+        assert_eq!(bot.receive().unwrap(), None);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 101);
+        assert_eq!(lines[0], r#"{"id":0,"tick":0,"score":0,"games":[]}"#);
+        assert!(
+            lines[100].starts_with(r#"{"id":100,"tick":100,"score":"#),
+            "{}",
+            lines[100]
+        );
+        assert!(lines[100].contains(r#""game":"trex","colliding":"#));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bot_that_stops_reading_is_disconnected() {
+        let path = std::env::temp_dir().join("miniterms-bot-not-reading.sock");
+        let mut socket = BotSocket::bind(&path, Duration::from_millis(1)).unwrap();
+        let _bot = UnixStream::connect(&path).unwrap();
+        let run = Run::new(RunConfig::new());
+
+        // The states fill the buffer of the socket, until sending one times out
+        assert_eq!(socket.exchange(&run), None);
+        assert!(socket.is_connected());
+        let exchanges = (0..100_000).position(|_| socket.exchange(&run) == Some(0));
+        assert!(exchanges.is_some());
+        assert!(!socket.is_connected());
+    }
+}
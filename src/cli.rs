@@ -0,0 +1,339 @@
+use crate::games::GameKind;
+use crate::run_code;
+use crate::run_config::{parse_games, Difficulty, RunConfig, SolutionSource};
+use crate::verify::VerifyOptions;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: miniterms [OPTIONS]
+       miniterms verify [VERIFY OPTIONS]
+
+Options:
+  --seed <TEXT>        Seed of the run
+  --games <LIST>       Comma separated games to play, starts the run right away
+                       (trex, tetris, breakout, defend, space, packman)
+  --solution <NAME>    Strategy that generates the solution (phased, markov, rhythmic)
+  --solution-from <PATH>
+                       Use the keys of a solution or replay file as the solution
+  --export-solution <PATH>
+                       Write the keys of the solution to a file while playing
+  --unlock-ticks <N>   A game joins a gauntlet run every N ticks [default: 1125]
+  --unlock-score <N>   Or every N points, whichever comes first [default: 15]
+  --code <CODE>        Play the run shared with a run code
+  --daily              Play today's daily challenge
+  --resume             Resume the saved run
+  --save <PATH>        File where the run is saved from the pause menu and resumed from
+                       [default: ~/.local/share/miniterms/save]
+  --fps <N>            Frames drawn per second [default: 25]
+  --tick-rate <MS>     Milliseconds per simulation tick [default: 40]
+  --log-file <PATH>    Append the log to a file (also MINITERMS_LOG_FILE)
+  --record <PATH>      Record the player input of the run to a replay file
+  --replay <PATH>      Play back a replay file instead of reading the keyboard
+  --headless           Simulate the replay without a terminal and print the result,
+                       fails if the simulation diverges from the recording
+  --bot                Let a program play the run through stdin and stdout instead of
+                       the terminal, a tick each time it replies
+  --bot-socket <PATH>  Let a program play through a Unix socket while the run is shown
+  -h, --help           Print this help
+
+Environment:
+  MINITERMS_LOG_LEVEL  Lowest level kept in the log (trace, debug, info, warn, error)
+                       [default: trace]
+
+Verify options, check that following the solution survives every game:
+  --games <LIST>       Games to verify [default: all]
+  --difficulty <NAME>  Only verify a difficulty (easy, normal, hard)
+  --solution <NAME>    Strategy that generates the solution [default: phased]
+  --seeds <N>          Seeds verified for each game and difficulty [default: 10]
+  --ticks <N>          Ticks simulated for each seed [default: 10000]";
+
+pub struct Options {
+    pub seed: Option<String>,
+    pub games: Option<Vec<GameKind>>,
+    pub solution: Option<SolutionSource>,
+    pub unlock_ticks: Option<usize>,
+    pub unlock_score: Option<usize>,
+    /// Run decoded from `--code`
+    pub code: Option<RunConfig>,
+    pub daily: bool,
+    pub resume: bool,
+    pub save: Option<PathBuf>,
+    pub frame_time: Duration,
+    pub tick_rate: Duration,
+    pub log_file: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub export_solution: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+    /// Play with the input of a bot through stdin and stdout
+    pub bot: bool,
+    pub bot_socket: Option<PathBuf>,
+}
+
+pub enum Command {
+    Run(Box<Options>),
+    Verify(VerifyOptions),
+    Help,
+}
+
+impl Options {
+    fn new() -> Self {
+        Self {
+            seed: None,
+            games: None,
+            solution: None,
+            unlock_ticks: None,
+            unlock_score: None,
+            code: None,
+            daily: false,
+            resume: false,
+            save: None,
+            frame_time: Duration::from_millis(40),
+            tick_rate: Duration::from_millis(40),
+            log_file: None,
+            record: None,
+            export_solution: None,
+            replay: None,
+            headless: false,
+            bot: false,
+            bot_socket: None,
+        }
+    }
+}
+
+impl Options {
+    /// Configuration of the run given by `--games`, `--seed`, `--solution` and `--unlock-*`,
+    /// the rest is the default one
+    pub fn config(&self) -> RunConfig {
+        let mut config = RunConfig::new();
+        if let Some(games) = &self.games {
+            config.games = games.clone();
+        }
+        if let Some(seed) = &self.seed {
+            config.seed = seed.clone();
+        }
+        if let Some(solution) = &self.solution {
+            config.solution = solution.clone();
+        }
+        if let Some(ticks) = self.unlock_ticks {
+            config.unlock.ticks = ticks;
+        }
+        if let Some(score) = self.unlock_score {
+            config.unlock.score = score;
+        }
+        config
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+
+    if args.peek().map(String::as_str) == Some("verify") {
+        args.next();
+        return parse_verify(args);
+    }
+
+    let mut options = Options::new();
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_arg(&arg);
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(format!("missing value for '{name}'"))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" | "--headless" | "--daily" | "--resume" | "--bot"
+                if inline_value.is_some() =>
+            {
+                return Err(format!("'{name}' does not take a value"));
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            "--seed" => options.seed = Some(value()?),
+            "--games" => {
+                let games = parse_games(&value()?)?;
+                options.games = Some(games);
+            }
+            "--solution" => options.solution = Some(parse_solution(&value()?)?),
+            "--solution-from" => {
+                options.solution = Some(SolutionSource::Recording(value()?.into()));
+            }
+            "--unlock-ticks" => {
+                options.unlock_ticks = Some(parse_positive(&name, &value()?)? as usize);
+            }
+            "--unlock-score" => {
+                options.unlock_score = Some(parse_positive(&name, &value()?)? as usize);
+            }
+            "--code" => options.code = Some(run_code::decode(&value()?)?),
+            "--daily" => options.daily = true,
+            "--resume" => options.resume = true,
+            "--save" => options.save = Some(value()?.into()),
+            "--fps" => {
+                let fps = parse_positive(&name, &value()?)?;
+                options.frame_time = Duration::from_secs(1) / fps;
+            }
+            "--tick-rate" => {
+                let millis = parse_positive(&name, &value()?)?;
+                options.tick_rate = Duration::from_millis(millis as u64);
+            }
+            "--log-file" => options.log_file = Some(value()?.into()),
+            "--record" => options.record = Some(value()?.into()),
+            "--export-solution" => options.export_solution = Some(value()?.into()),
+            "--replay" => options.replay = Some(value()?.into()),
+            "--headless" => options.headless = true,
+            "--bot" => options.bot = true,
+            "--bot-socket" => options.bot_socket = Some(value()?.into()),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    if options.headless && options.replay.is_none() {
+        return Err("'--headless' requires '--replay <PATH>'".to_string());
+    }
+
+    // Each of these options replaces the terminal or the keyboard
+    let interfaces = [
+        ("--headless", options.headless),
+        ("--bot", options.bot),
+        ("--bot-socket", options.bot_socket.is_some()),
+    ];
+    let chosen: Vec<_> = interfaces.iter().filter(|(_, set)| *set).collect();
+    if let [(first, _), (second, _), ..] = chosen[..] {
+        return Err(format!("'{first}' can not be combined with '{second}'"));
+    }
+    if options.bot && options.replay.is_some() {
+        return Err("'--bot' can not be combined with '--replay'".to_string());
+    }
+    if options.resume && options.record.is_some() {
+        return Err(
+            "'--record' can not be combined with '--resume', replays start from the first tick"
+                .to_string(),
+        );
+    }
+
+    // Each of these options chooses the run on its own
+    let run_choices = [
+        ("--replay", options.replay.is_some()),
+        ("--code", options.code.is_some()),
+        ("--daily", options.daily),
+        ("--resume", options.resume),
+        (
+            "--games/--seed/--solution/--unlock-*",
+            options.games.is_some()
+                || options.seed.is_some()
+                || options.solution.is_some()
+                || options.unlock_ticks.is_some()
+                || options.unlock_score.is_some(),
+        ),
+    ];
+    let chosen: Vec<_> = run_choices.iter().filter(|(_, set)| *set).collect();
+    if let [(first, _), (second, _), ..] = chosen[..] {
+        return Err(format!("'{first}' can not be combined with '{second}'"));
+    }
+
+    Ok(Command::Run(Box::new(options)))
+}
+
+fn parse_verify(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = VerifyOptions::new();
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_arg(&arg);
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or(format!("missing value for '{name}'"))
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--games" => options.games = parse_games(&value()?)?,
+            "--difficulty" => {
+                let name = value()?;
+                let difficulty =
+                    Difficulty::from_name(&name).ok_or(format!("unknown difficulty '{name}'"))?;
+                options.difficulties = vec![difficulty];
+            }
+            "--solution" => options.solution = parse_solution(&value()?)?,
+            "--seeds" => options.seeds = parse_positive(&name, &value()?)? as usize,
+            "--ticks" => options.ticks = parse_positive(&name, &value()?)? as usize,
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    Ok(Command::Verify(options))
+}
+
+/// Accepts both `--option value` and `--option=value`
+fn split_arg(arg: &str) -> (String, Option<String>) {
+    match arg.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => {
+            (name.to_string(), Some(value.to_string()))
+        }
+        _ => (arg.to_string(), None),
+    }
+}
+
+fn parse_solution(name: &str) -> Result<SolutionSource, String> {
+    SolutionSource::GENERATED
+        .into_iter()
+        .find(|source| source.name().eq_ignore_ascii_case(name))
+        .ok_or(format!("unknown solution '{name}'"))
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "invalid value '{value}' for '{name}': expected a positive integer"
+        )),
+        Ok(number) => Ok(number),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_args(args: &str) -> Result<Command, String> {
+        parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn options() {
+        let Ok(Command::Run(options)) = parse_args("--fps=50 --tick-rate 20 --games trex,space")
+        else {
+            panic!("expected the options to parse");
+        };
+        assert_eq!(options.frame_time, Duration::from_millis(20));
+        assert_eq!(options.tick_rate, Duration::from_millis(20));
+        assert_eq!(options.games, Some(vec![GameKind::TRex, GameKind::Space]));
+
+        assert!(matches!(parse_args("--help"), Ok(Command::Help)));
+        assert!(parse_args("--fps 0").is_err());
+        assert!(parse_args("--games chess").is_err());
+        assert!(parse_args("--headless").is_err());
+        assert!(parse_args("--replay a --seed b").is_err());
+        assert!(parse_args("--daily --games trex").is_err());
+        assert!(parse_args("--resume --code a").is_err());
+        assert!(parse_args("--resume --record a").is_err());
+        assert!(parse_args("--games trex --seed b").is_ok());
+        assert!(parse_args("--unlock-ticks 0").is_err());
+        assert!(parse_args("--code a --unlock-score 5").is_err());
+        assert!(parse_args("--bot --games trex").is_ok());
+        assert!(parse_args("--bot --bot-socket a").is_err());
+        assert!(parse_args("--bot --replay a").is_err());
+
+        let Ok(Command::Verify(verify)) = parse_args("verify --games trex --seeds=3") else {
+            panic!("expected the verify options to parse");
+        };
+        assert_eq!(verify.games, [GameKind::TRex]);
+        assert_eq!(verify.seeds, 3);
+        assert!(parse_args("verify --record a").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::run_config::RunConfig;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// Run that is the same for everyone playing on `date`
+pub fn daily_config(date: Date) -> RunConfig {
+    let mut config = RunConfig::new();
+    config.seed = format!("daily-{date}");
+    config
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Date {
+    /// Current date in the local timezone
+    pub fn today() -> Date {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs() as i64);
+
+        let days = (now + utc_offset(now)).div_euclid(24 * 60 * 60);
+        Date::from_days(days)
+    }
+
+    /// Date `days` after 1970-01-01, from <https://howardhinnant.github.io/date_algorithms.html>
+    fn from_days(days: i64) -> Date {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+        Date {
+            year: year as i32,
+            month,
+            day,
+        }
+    }
+}
+
+/// Seconds to add to UTC to get the local time
+#[cfg(unix)]
+fn utc_offset(time: i64) -> i64 {
+    let time = time as libc::time_t;
+
+    // SAFETY: `localtime_r` only writes to the given `tm`, which is valid for any bit pattern
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            0
+        } else {
+            tm.tm_gmtoff as i64
+        }
+    }
+}
+
+/// Seconds to add to UTC to get the local time
+#[cfg(not(unix))]
+fn utc_offset(_time: i64) -> i64 {
+    0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dates_from_days() {
+        let date = |year, month, day| Date { year, month, day };
+        assert_eq!(Date::from_days(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days(11016), date(2000, 2, 29));
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(date(2024, 3, 7).to_string(), "2024-03-07");
+    }
+}
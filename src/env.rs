@@ -0,0 +1,239 @@
+//! The games as reinforcement learning environments, in the style of Gym
+//!
+//! ```no_run
+//! use miniterms::env::{Difficulty, Environment, GameKind, Key, ObservationKind, Size};
+//!
+//! let size = Size::new(240, 112);
+//! let mut env = Environment::new(GameKind::TRex, Difficulty::Normal, ObservationKind::State, size)
+//!     .unwrap();
+//!
+//! let mut observation = env.reset(42);
+//! let mut total = 0.;
+//! loop {
+//!     let keys: &[Key] = if bot(&observation) { &[Key::Space] } else { &[] };
+//!     let (next, reward, done) = env.step(keys);
+//!     observation = next;
+//!     total += reward;
+//!     if done {
+//!         break;
+//!     }
+//! }
+//! # fn bot(_: &miniterms::env::Observation) -> bool { true }
+//! ```
+
+use crate::games::trex::TRexGame;
+use crate::games::GameContext;
+use crate::input::Keys;
+use crate::run_config::SolutionSource;
+use crate::seed::Seed;
+use crate::solution::{self, Solution};
+
+pub use crate::games::trex::STATE_LEN as TREX_STATE_LEN;
+pub use crate::games::GameKind;
+pub use crate::input::Key;
+pub use crate::math::Size;
+pub use crate::pixel_canvas::Bitmap;
+pub use crate::run_config::Difficulty;
+
+/// Reward of every tick the player survives
+const SURVIVAL_REWARD: f32 = 1.;
+
+/// How the environment describes the game
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ObservationKind {
+    /// The pixels of the game, drawn like in the terminal but without colors
+    Pixels,
+    /// [`TREX_STATE_LEN`] numbers that describe the game
+    ///
+    /// The first three are the player: its height above the ground relative to the height of
+    /// the game, then 1 if it is jumping and 1 if it is crouching, 0 otherwise. Then four
+    /// numbers for each of the three closest enemies on screen, sorted from left to right:
+    /// their distance from the left border and their height above the ground, both relative to
+    /// the size of the game, the pixels they move left each tick relative to the width, and 1
+    /// for a bird or 0 for a cactus. Missing enemies are `[1, 0, 0, 0]`, as if they were at
+    /// the right border.
+    State,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Observation {
+    Pixels(Bitmap),
+    State(Vec<f32>),
+}
+
+/// A single game, simulated one tick per step without a terminal
+///
+/// The player gets a reward of 1 for every tick it survives and 0 for the tick it dies, which
+/// ends the episode. In T-Rex, holding [`Key::Space`] jumps and holding [`Key::Down`] crouches,
+/// or makes a short jump when both are held. The other keys do nothing.
+///
+/// The enemies only depend on the seed of the episode, not on the keys. They are spaced so
+/// each one can be jumped over after landing from the previous one.
+pub struct Environment {
+    difficulty: Difficulty,
+    observation: ObservationKind,
+    /// Pixels of the game
+    size: Size<u16>,
+    trex: TRexGame,
+    /// Drives the ghost of the game, which is not part of the observations
+    ///
+    /// It does not change which enemies spawn, the planner of the solution dodges them like the
+    /// player has to.
+    solution: Solution,
+    keys: Keys,
+    ticks: usize,
+    done: bool,
+}
+
+impl Environment {
+    /// Fails for the games that can not be played yet
+    pub fn new(
+        game: GameKind,
+        difficulty: Difficulty,
+        observation: ObservationKind,
+        size: Size<u16>,
+    ) -> Result<Environment, String> {
+        if game != GameKind::TRex {
+            return Err(format!("{} can not be played yet", game.name()));
+        }
+
+        let seed = Seed::from_value(0);
+        Ok(Environment {
+            difficulty,
+            observation,
+            size,
+            trex: TRexGame::new(&seed),
+            solution: new_solution(&seed, difficulty),
+            keys: Keys::new(),
+            ticks: 0,
+            done: false,
+        })
+    }
+
+    /// Starts a new episode, the same seed always plays the same game
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let seed = Seed::from_value(seed);
+        self.trex = TRexGame::new(&seed);
+        self.solution = new_solution(&seed, self.difficulty);
+        self.keys = Keys::new();
+        self.ticks = 0;
+        self.done = false;
+
+        self.observe()
+    }
+
+    /// Simulates a tick while holding `keys`, returns the observation after it, the reward
+    /// and whether the episode is done
+    ///
+    /// Once the episode is done the game does not change until it is reset.
+    pub fn step(&mut self, keys: &[Key]) -> (Observation, f32, bool) {
+        if self.done {
+            return (self.observe(), 0., true);
+        }
+
+        for key in Key::ALL {
+            match (keys.contains(&key), self.keys.pressing(key)) {
+                (true, false) => self.keys.press(key),
+                (false, true) => self.keys.release(key),
+                _ => {}
+            }
+        }
+
+        self.trex.update(&mut GameContext {
+            size: self.size,
+            keys: self.keys,
+            solution: &self.solution,
+            difficulty: self.difficulty,
+        });
+        self.keys.update();
+        self.solution.update();
+        self.ticks += 1;
+
+        self.done = self.trex.is_colliding();
+        let reward = if self.done { 0. } else { SURVIVAL_REWARD };
+
+        (self.observe(), reward, self.done)
+    }
+
+    pub fn observe(&self) -> Observation {
+        match self.observation {
+            ObservationKind::Pixels => {
+                let mut bitmap = Bitmap::new(TRexGame::canvas_origin(self.size), self.size);
+                self.trex.draw(&mut bitmap, false);
+                Observation::Pixels(bitmap)
+            }
+            ObservationKind::State => Observation::State(self.trex.state(self.size)),
+        }
+    }
+
+    /// Ticks simulated since the last reset
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    /// Points of the game, like in the terminal
+    pub fn score(&self) -> usize {
+        self.trex.score()
+    }
+}
+
+fn new_solution(seed: &Seed, difficulty: Difficulty) -> Solution {
+    Solution::new(solution::strategy(
+        &SolutionSource::Phased,
+        seed,
+        difficulty,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn episode(observation: ObservationKind, seed: u64) -> (Vec<Observation>, f32) {
+        let size = Size::new(240, 112);
+        let mut env = Environment::new(GameKind::TRex, Difficulty::Normal, observation, size)
+            .expect("T-Rex can be played");
+
+        let mut observations = vec![env.reset(seed)];
+        let mut total = 0.;
+        for tick in 0..2000 {
+            let keys: &[Key] = if tick % 30 < 3 { &[Key::Space] } else { &[] };
+            let (observation, reward, done) = env.step(keys);
+            observations.push(observation);
+            total += reward;
+            if done {
+                break;
+            }
+        }
+        (observations, total)
+    }
+
+    #[test]
+    fn episodes() {
+        let (states, reward) = episode(ObservationKind::State, 7);
+        assert!(reward > 0.);
+        assert_eq!(episode(ObservationKind::State, 7), (states.clone(), reward));
+        assert!(states.iter().all(|state| match state {
+            Observation::State(state) => state.len() == TREX_STATE_LEN,
+            Observation::Pixels(_) => false,
+        }));
+
+        let (pixels, _) = episode(ObservationKind::Pixels, 7);
+        let Observation::Pixels(bitmap) = &pixels[0] else {
+            panic!("expected pixels");
+        };
+        assert_eq!(bitmap.size(), Size::new(240, 112));
+        assert!(
+            bitmap.bytes().iter().any(|&byte| byte != 0),
+            "the player is drawn"
+        );
+
+        assert!(Environment::new(
+            GameKind::Tetris,
+            Difficulty::Normal,
+            ObservationKind::State,
+            Size::new(10, 10)
+        )
+        .is_err());
+    }
+}
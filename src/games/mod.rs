@@ -1,15 +1,97 @@
 use crate::input::Keys;
+use crate::layout::PanelSpec;
 use crate::math::Size;
+use crate::run_config::Difficulty;
 use crate::solution::Solution;
-use std::fmt::Write;
 
 pub mod tetris;
 pub mod trex;
 
 mod utils;
 
+/// Game that the solution planner can simulate ahead of time
+pub trait Plannable {
+    fn clone_box(&self) -> Box<dyn Plannable>;
+
+    /// Advances the world and the solution player a tick, as if the solution was `keys`
+    ///
+    /// Returns false if the solution player has died.
+    fn simulate(&mut self, keys: Keys, size: Size<u16>, difficulty: Difficulty) -> bool;
+
+    /// Hash of the state that depends on the keys
+    ///
+    /// Simulations of the same game with equal hashes at the same tick must behave the same.
+    fn player_state(&self) -> u64;
+}
+
 pub struct GameContext<'a> {
     pub size: Size<u16>,
     pub keys: Keys,
     pub solution: &'a Solution,
+    pub difficulty: Difficulty,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GameKind {
+    TRex,
+    Tetris,
+    Breakout,
+    DefendThePlanet,
+    Space,
+    Packman,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 6] = [
+        GameKind::TRex,
+        GameKind::Tetris,
+        GameKind::Breakout,
+        GameKind::DefendThePlanet,
+        GameKind::Space,
+        GameKind::Packman,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameKind::TRex => "T-Rex",
+            GameKind::Tetris => "Tetris",
+            GameKind::Breakout => "Breakout",
+            GameKind::DefendThePlanet => "Defend the Planet",
+            GameKind::Space => "Space",
+            GameKind::Packman => "Packman",
+        }
+    }
+
+    /// Short name used to store the game in files and codes
+    pub fn id(self) -> &'static str {
+        match self {
+            GameKind::TRex => "trex",
+            GameKind::Tetris => "tetris",
+            GameKind::Breakout => "breakout",
+            GameKind::DefendThePlanet => "defend",
+            GameKind::Space => "space",
+            GameKind::Packman => "packman",
+        }
+    }
+
+    pub fn panel_spec(self) -> PanelSpec {
+        let (aspect, min_width, min_height) = match self {
+            GameKind::TRex => (4., 30, 8),
+            // 10 columns of pieces that are 2 characters wide
+            GameKind::Tetris => (1., 22, 12),
+            GameKind::Breakout => (1.5, 20, 10),
+            GameKind::DefendThePlanet => (2., 20, 10),
+            GameKind::Space => (2.5, 24, 8),
+            GameKind::Packman => (2., 20, 10),
+        };
+
+        PanelSpec {
+            aspect,
+            min_size: Size::new(min_width, min_height),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<GameKind> {
+        GameKind::ALL.into_iter().find(|game| game.id() == id)
+    }
 }
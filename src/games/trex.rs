@@ -1,73 +1,243 @@
 use super::utils::Parabola;
-use super::GameContext;
+use super::{GameContext, GameKind, Plannable};
 use crate::image::{Image, ImageAnimation, Origin, Sprite};
 use crate::input::{Key, Keys};
-use crate::log;
-use crate::math::Pos;
-use crate::pixel_canvas::PixelCanvas;
-use crate::solution::Solution;
-use rand::rngs::SmallRng;
-use rand::{Rng, RngCore, SeedableRng};
+use crate::log::{debug, info};
+use crate::math::{Pos, Size};
+use crate::pixel_canvas::DrawTarget;
+use crate::run_config::Difficulty;
+use crate::save::{StateReader, StateWriter};
+use crate::seed::Seed;
+use crate::solution::{Lookahead, SOLUTION_SIZE};
+use rand::{Rng, RngCore};
+use rand_chacha::ChaCha8Rng;
 use ratatui::style::Color;
 use std::collections::VecDeque;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-#[derive(Copy, Clone)]
+/// Enemies described by [`TRexGame::state`]
+pub const OBSERVED_ENEMIES: usize = 3;
+/// Numbers in the structured state of the game
+pub const STATE_LEN: usize = 3 + 4 * OBSERVED_ENEMIES;
+/// Ticks between two enemies reaching the T-Rex, enough to land from a jump and jump again
+const MIN_ARRIVAL_GAP: i32 = 24;
+
+#[derive(Copy, Clone, Hash)]
 enum EnemyModel {
     Cactus { model: u8 },
     Bird,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Hash)]
 struct Enemy {
     position: Pos<i32>,
     velocity: u8,
     model: EnemyModel,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct TRex {
     jump: Option<Parabola>,
     crouching: bool,
 }
 
+#[derive(Clone)]
 pub struct TRexGame {
     trex: TRex,
+    world: TRexWorld,
+    /// The player is touching an enemy
+    colliding: bool,
+    /// Future states of the solution T-Rex
+    lookahead: Lookahead<TRex>,
+}
+
+/// Everything but the player, which is what the solution planner simulates
+#[derive(Clone)]
+pub struct TRexWorld {
     trex_solution: TRex,
     enemies: VecDeque<Enemy>,
     enemy_cooldown: u16,
-    random: SmallRng,
+    random: ChaCha8Rng,
     frame_count: usize,
+    /// Enemies that have left the screen
+    dodged: usize,
 }
 
 impl TRexGame {
-    pub fn new() -> Self {
+    pub fn new(seed: &Seed) -> Self {
         let initial_trex = TRex {
             crouching: false,
             jump: None,
         };
         TRexGame {
             trex: initial_trex,
-            trex_solution: initial_trex,
-            frame_count: 0,
-            enemies: VecDeque::new(),
-            enemy_cooldown: 10,
-            random: SmallRng::from_seed(*b"Seed chosen by a fair dice roll."),
+            world: TRexWorld {
+                trex_solution: initial_trex,
+                frame_count: 0,
+                dodged: 0,
+                enemies: VecDeque::new(),
+                enemy_cooldown: 10,
+                random: seed.rng(GameKind::TRex.id()),
+            },
+            colliding: false,
+            lookahead: Lookahead::new(),
         }
     }
 
     pub fn update(&mut self, game: &mut GameContext) {
         self.trex.update(game.keys);
-        self.trex_solution.update(game.solution.keys(0));
+        let spawned = self
+            .world
+            .update(game.solution.keys(0), game.size, game.difficulty);
+        self.lookahead
+            .sync(self.world.trex_solution, game.solution, TRex::update);
+
+        // The planner simulates the same enemies, so the solution should always get past them
+        if let Some(enemy) = spawned.then(|| self.world.enemies.back()).flatten() {
+            if !self.solution_dodges(enemy) {
+                debug!(
+                    "The solution runs into the enemy spawned at tick {}",
+                    self.world.frame_count
+                );
+            }
+        }
+
+        let colliding = self.world.collide(&self.trex, 0);
+        if colliding && !self.colliding {
+            info!("Game Over");
+        }
+        self.colliding = colliding;
+
+        self.world.frame_count += 1;
+    }
+
+    pub fn score(&self) -> usize {
+        self.world.dodged
+    }
+
+    pub fn is_colliding(&self) -> bool {
+        self.colliding
+    }
+
+    pub fn world(&self) -> &TRexWorld {
+        &self.world
+    }
+
+    /// Copy of the game without the lookahead, which is simulated again on the next update
+    pub fn snapshot(&self) -> TRexGame {
+        TRexGame {
+            trex: self.trex,
+            world: self.world.clone(),
+            colliding: self.colliding,
+            lookahead: Lookahead::new(),
+        }
+    }
+
+    /// Structured state of the game in `size` pixels, made of [`STATE_LEN`] numbers
+    ///
+    /// The height of the player, whether it is jumping and whether it is crouching, followed by
+    /// the position, speed and kind of the [`OBSERVED_ENEMIES`] closest enemies that have not
+    /// been dodged yet. Positions and speeds are relative to `size`, missing enemies are as far
+    /// as the right border.
+    pub fn state(&self, size: Size<u16>) -> Vec<f32> {
+        let width = size.width.max(1) as f32;
+        let height = size.height.max(1) as f32;
+
+        let mut state = vec![
+            self.trex.pos().1 as f32 / height,
+            self.trex.jump.is_some() as u8 as f32,
+            self.trex.crouching as u8 as f32,
+        ];
+
+        let frame_count = self.world.frame_count;
+        let mut enemies: Vec<_> = (self.world.enemies.iter())
+            .filter(|enemy| enemy.position.x + enemy.skin(frame_count).width() as i32 >= 0)
+            .collect();
+        enemies.sort_by_key(|enemy| enemy.position.x);
+
+        for i in 0..OBSERVED_ENEMIES {
+            match enemies.get(i) {
+                Some(enemy) => state.extend([
+                    enemy.position.x as f32 / width,
+                    enemy.position.y as f32 / height,
+                    enemy.velocity as f32 / width,
+                    matches!(enemy.model, EnemyModel::Bird) as u8 as f32,
+                ]),
+                None => state.extend([1., 0., 0., 0.]),
+            }
+        }
+
+        state
+    }
+
+    /// Position in a canvas of `size` pixels where the game is drawn
+    pub fn canvas_origin(size: Size<u16>) -> Pos<i32> {
+        Pos::new(20, size.height as i32 - 1)
+    }
+
+    /// Solution T-Rex after following the solution for `time` more ticks
+    fn trex_solution(&self, time: usize) -> TRex {
+        self.lookahead.get(time)
+    }
+
+    /// Whether the solution T-Rex gets past `enemy`, which spawns this tick
+    fn solution_dodges(&self, enemy: &Enemy) -> bool {
+        for time in 0..SOLUTION_SIZE {
+            let frame_count = self.world.frame_count + time;
+            let mut enemy = *enemy;
+            enemy.position.x -= enemy.velocity as i32 * time as i32;
+
+            if enemy.position.x + (enemy.skin(frame_count).width() as i32) < 0 {
+                return true;
+            }
+
+            let trex = self.trex_solution(time).sprite(frame_count);
+            if enemy.sprite(frame_count).collide(&trex) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl TRexWorld {
+    /// Updates everything but the player, returns whether a new enemy has spawned
+    ///
+    /// The enemies only depend on the seed, so the planner knows them ahead of time.
+    fn update(&mut self, solution_keys: Keys, size: Size<u16>, difficulty: Difficulty) -> bool {
+        self.trex_solution.update(solution_keys);
 
         self.despawn_enemies();
-        self.spawn_enemies(game);
         self.update_enemies();
+        self.spawn_enemies(size, difficulty)
+    }
 
-        if self.collide(&self.trex, 0) {
-            // log!("Game Over");
+    /// Spawns an enemy once the cooldown is over, returns whether it did
+    ///
+    /// Enemies that would reach the T-Rex too soon after the previous one are not spawned,
+    /// another one is tried in the next tick.
+    fn spawn_enemies(&mut self, size: Size<u16>, difficulty: Difficulty) -> bool {
+        if self.enemy_cooldown > 0 {
+            self.enemy_cooldown -= 1;
+            return false;
         }
 
-        self.frame_count += 1;
+        let cooldown = self.random.gen_range(10..50) as f32 / difficulty.speed();
+        let enemy = self.random_enemy(size);
+
+        let trex_x = self.trex_solution.pos().0;
+        let arrival = |enemy: &Enemy| (enemy.position.x - trex_x) / enemy.velocity as i32;
+        if let Some(last) = self.enemies.back() {
+            if arrival(&enemy) - arrival(last) < MIN_ARRIVAL_GAP {
+                return false;
+            }
+        }
+
+        self.enemies.push_back(enemy);
+        self.enemy_cooldown = cooldown.round() as u16 - 1;
+
+        true
     }
 
     fn collide(&self, trex: &TRex, time: usize) -> bool {
@@ -80,47 +250,33 @@ impl TRexGame {
         })
     }
 
-    fn spawn_cactus(&mut self, game: &mut GameContext) {
-        // TODO: Check game solution
-
-        self.enemies.push_back(Enemy {
-            position: Pos::new(game.size.width as i32, 0),
+    fn cactus(size: Size<u16>) -> Enemy {
+        Enemy {
+            position: Pos::new(size.width as i32, 0),
             velocity: 3,
             model: EnemyModel::Cactus { model: 0 },
-        });
+        }
     }
 
-    fn spawn_bird(&mut self, game: &mut GameContext) {
-        // TODO: Check game solution
-
-        let x = game.size.width as i32;
+    fn random_bird(&mut self, size: Size<u16>) -> Enemy {
+        let x = size.width as i32;
         let y = self.random.gen_range(1..=20);
 
-        self.enemies.push_back(Enemy {
+        Enemy {
             position: Pos { x, y },
             velocity: self.random.gen_range(4..=7),
             model: EnemyModel::Bird,
-        });
+        }
     }
 
-    fn spawn_enemy(&mut self, game: &mut GameContext) {
+    fn random_enemy(&mut self, size: Size<u16>) -> Enemy {
         let spawn_cactus = self.random.next_u32() & 3 != 0 || self.frame_count < 100;
 
         if spawn_cactus {
-            self.spawn_cactus(game);
+            TRexWorld::cactus(size)
         } else {
-            self.spawn_bird(game);
-        }
-    }
-
-    fn spawn_enemies(&mut self, game: &mut GameContext) {
-        if self.enemy_cooldown == 0 {
-            self.enemy_cooldown = self.random.gen_range(10..50);
-
-            self.spawn_enemy(game);
+            self.random_bird(size)
         }
-
-        self.enemy_cooldown -= 1;
     }
 
     fn update_enemies(&mut self) {
@@ -135,22 +291,138 @@ impl TRexGame {
         if let Some(enemy) = self.enemies.front() {
             if enemy.position.x < DESPAWN_Y_BARRIER {
                 self.enemies.pop_front();
+                self.dodged += 1;
             }
         }
     }
+}
+
+impl TRexGame {
+    /// Writes the state of the game, except for the lookahead which is simulated again
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.line("trex");
+        self.trex.save_state(state);
+        state.value(self.colliding);
+
+        let world = &self.world;
+        state.line("trex-world");
+        world.trex_solution.save_state(state);
+        state
+            .value(world.enemy_cooldown)
+            .value(world.frame_count)
+            .value(world.dodged)
+            .value(world.enemies.len())
+            .rng(&world.random);
+
+        for enemy in &world.enemies {
+            let model = match enemy.model {
+                EnemyModel::Cactus { model } => format!("cactus:{model}"),
+                EnemyModel::Bird => "bird".to_string(),
+            };
+            state
+                .line("trex-enemy")
+                .value(enemy.position.x)
+                .value(enemy.position.y)
+                .value(enemy.velocity)
+                .value(model);
+        }
+    }
 
-    #[allow(unused)]
-    fn trex_solution(&self, solution: &Solution, time: usize) -> TRex {
-        let mut trex = self.trex_solution;
-        for t in 1..=time {
-            let keys = solution.keys(t);
-            trex.update(keys);
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("trex")?;
+        self.trex = TRex::load_state(state)?;
+        self.colliding = state.value()?;
+
+        state.line("trex-world")?;
+        let world = &mut self.world;
+        world.trex_solution = TRex::load_state(state)?;
+        world.enemy_cooldown = state.value()?;
+        world.frame_count = state.value()?;
+        world.dodged = state.value()?;
+        let enemies: usize = state.value()?;
+        world.random = state.rng()?;
+
+        world.enemies.clear();
+        for _ in 0..enemies {
+            state.line("trex-enemy")?;
+            let position = Pos::new(state.value()?, state.value()?);
+            let velocity = state.value()?;
+            let model = match state.value::<String>()?.as_str() {
+                "bird" => EnemyModel::Bird,
+                text => match text.strip_prefix("cactus:").and_then(|m| m.parse().ok()) {
+                    Some(model) => EnemyModel::Cactus { model },
+                    None => return Err(format!("invalid enemy '{text}'")),
+                },
+            };
+            world.enemies.push_back(Enemy {
+                position,
+                velocity,
+                model,
+            });
         }
-        trex
+
+        self.lookahead = Lookahead::new();
+        Ok(())
+    }
+}
+
+/// State of the game, to check that the simulation is deterministic
+impl Hash for TRexGame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The lookahead only depends on the solution
+        self.trex.hash(state);
+        self.world.hash(state);
+        self.colliding.hash(state);
+    }
+}
+
+impl Hash for TRexWorld {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trex_solution.hash(state);
+        self.enemies.hash(state);
+        self.enemy_cooldown.hash(state);
+        self.random.get_seed().hash(state);
+        self.random.get_word_pos().hash(state);
+        self.frame_count.hash(state);
+        self.dodged.hash(state);
+    }
+}
+
+impl Plannable for TRexWorld {
+    fn clone_box(&self) -> Box<dyn Plannable> {
+        Box::new(self.clone())
+    }
+
+    fn simulate(&mut self, keys: Keys, size: Size<u16>, difficulty: Difficulty) -> bool {
+        self.update(keys, size, difficulty);
+        let alive = !self.collide(&self.trex_solution, 0);
+        self.frame_count += 1;
+        alive
+    }
+
+    fn player_state(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.trex_solution.hash(&mut hasher);
+        hasher.finish()
     }
 }
 
 impl TRex {
+    /// Writes whether it is crouching and the jump, `-` if it is not jumping
+    fn save_state(&self, state: &mut StateWriter) {
+        let jump = self.jump.map_or("-".to_string(), |jump| jump.to_string());
+        state.value(self.crouching).value(jump);
+    }
+
+    fn load_state(state: &mut StateReader) -> Result<TRex, String> {
+        let crouching = state.value()?;
+        let jump = match state.value::<String>()?.as_str() {
+            "-" => None,
+            jump => Some(jump.parse()?),
+        };
+        Ok(TRex { jump, crouching })
+    }
+
     fn pos(&self) -> (i32, i32) {
         const TREX_Y: i32 = 4;
         (TREX_Y, self.jump.as_ref().map_or(0, |p| p.value() as i32))
@@ -208,13 +480,17 @@ impl TRex {
 }
 
 impl TRexGame {
-    pub fn draw(&self, canvas: &mut PixelCanvas) {
-        canvas.draw(self.trex.sprite(self.frame_count));
-        canvas.draw(self.trex_solution.sprite(self.frame_count));
+    /// Draws the game, with the solution T-Rex as a ghost if `ghost` is set
+    pub fn draw(&self, canvas: &mut impl DrawTarget, ghost: bool) {
+        let frame_count = self.world.frame_count;
+        if ghost {
+            canvas.draw(self.world.trex_solution.sprite(frame_count).ghost());
+        }
+        canvas.draw(self.trex.sprite(frame_count));
 
         // Draw enemies
-        for enemy in &self.enemies {
-            canvas.draw(enemy.sprite(self.frame_count));
+        for enemy in &self.world.enemies {
+            canvas.draw(enemy.sprite(frame_count));
         }
     }
 }
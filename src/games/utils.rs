@@ -1,4 +1,7 @@
-#[derive(Copy, Clone)]
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Parabola {
     max: usize,
     duration: usize,
@@ -42,3 +45,31 @@ impl Parabola {
         (b * self.time - a * self.time * self.time) / (self.duration * self.duration)
     }
 }
+
+/// Written as `max:duration:time`
+impl Display for Parabola {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.max, self.duration, self.time)
+    }
+}
+
+impl FromStr for Parabola {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Parabola, String> {
+        let mut numbers = text.split(':').map(str::parse);
+        let (Some(Ok(max)), Some(Ok(duration)), Some(Ok(time)), None) = (
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+        ) else {
+            return Err(format!("invalid parabola '{text}'"));
+        };
+
+        let mut parabola = Parabola::new(max, duration);
+        parabola.time = time;
+        parabola.value = parabola.calc_value();
+        Ok(parabola)
+    }
+}
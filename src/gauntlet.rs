@@ -0,0 +1,131 @@
+use crate::games::GameKind;
+use crate::log::info;
+use crate::replay;
+use crate::run_config::Unlock;
+use crate::save::{StateReader, StateWriter};
+use std::collections::VecDeque;
+
+/// Ticks between a game being announced and it joining the run, 3 seconds at the default
+/// tick rate
+pub const WARNING_TICKS: usize = 3 * 25;
+
+/// Run mode where the games join one by one
+#[derive(Clone, Hash)]
+pub struct Gauntlet {
+    pending: VecDeque<GameKind>,
+    unlock: Unlock,
+    unlocked: usize,
+    /// Ticks left until the first pending game joins
+    countdown: Option<usize>,
+}
+
+impl Gauntlet {
+    /// Returns the gauntlet and the games that are active from the start
+    pub fn new(games: &[GameKind], unlock: Unlock) -> (Gauntlet, Vec<GameKind>) {
+        let mut pending: VecDeque<_> = games.iter().copied().collect();
+
+        // Always start with T-Rex if it has been chosen
+        let first = match pending.iter().position(|&game| game == GameKind::TRex) {
+            Some(index) => pending.remove(index),
+            None => pending.pop_front(),
+        };
+
+        let gauntlet = Gauntlet {
+            pending,
+            unlock,
+            unlocked: 0,
+            countdown: None,
+        };
+
+        (gauntlet, first.into_iter().collect())
+    }
+
+    /// Must be called once per simulated tick, returns the game that joins the run
+    pub fn update(&mut self, ticks: usize, score: usize) -> Option<GameKind> {
+        let &next = self.pending.front()?;
+
+        match &mut self.countdown {
+            None => {
+                let stage = self.unlocked + 1;
+                if ticks >= stage * self.unlock.ticks || score >= stage * self.unlock.score {
+                    info!("{} joins in {WARNING_TICKS} ticks", next.name());
+                    self.countdown = Some(WARNING_TICKS);
+                }
+                None
+            }
+            Some(0) => {
+                self.countdown = None;
+                self.unlocked += 1;
+                self.pending.pop_front()
+            }
+            Some(countdown) => {
+                *countdown -= 1;
+                None
+            }
+        }
+    }
+
+    /// Game about to join and the ticks left until it does
+    pub fn warning(&self) -> Option<(GameKind, usize)> {
+        Some((*self.pending.front()?, self.countdown?))
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        let countdown = self
+            .countdown
+            .map_or("-".to_string(), |ticks| ticks.to_string());
+        state.line("gauntlet").value(self.unlocked).value(countdown);
+        for game in &self.pending {
+            state.value(game.id());
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("gauntlet")?;
+        self.unlocked = state.value()?;
+        self.countdown = match state.value::<String>()?.as_str() {
+            "-" => None,
+            ticks => Some(
+                ticks
+                    .parse()
+                    .map_err(|_| format!("invalid countdown '{ticks}'"))?,
+            ),
+        };
+        self.pending = replay::parse_games(state.rest())?.into();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn games_join_after_the_warning() {
+        let games = [GameKind::Tetris, GameKind::TRex, GameKind::Space];
+        let unlock = Unlock {
+            ticks: 100,
+            score: 15,
+        };
+        let (mut gauntlet, active) = Gauntlet::new(&games, unlock);
+        assert_eq!(active, [GameKind::TRex]);
+
+        assert_eq!(gauntlet.update(1, 0), None);
+        assert_eq!(gauntlet.warning(), None);
+
+        // Score threshold
+        assert_eq!(gauntlet.update(2, unlock.score), None);
+        assert_eq!(gauntlet.warning(), Some((GameKind::Tetris, WARNING_TICKS)));
+
+        let joined: Vec<_> = (0..=WARNING_TICKS)
+            .filter_map(|t| gauntlet.update(3 + t, unlock.score))
+            .collect();
+        assert_eq!(joined, [GameKind::Tetris]);
+
+        // Time threshold
+        let joined = (0..=2 * unlock.ticks + WARNING_TICKS + 1)
+            .find_map(|t| gauntlet.update(t, unlock.score));
+        assert_eq!(joined, Some(GameKind::Space));
+        assert_eq!(gauntlet.update(usize::MAX, usize::MAX), None);
+    }
+}
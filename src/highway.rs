@@ -0,0 +1,73 @@
+use crate::input::Key;
+use crate::solution::Solution;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    widgets::{Block, Widget},
+};
+
+/// Width of the highway, with the borders
+pub const HIGHWAY_WIDTH: u16 = 16;
+
+/// Ticks of the solution in each row
+const TICKS_PER_ROW: usize = 2;
+/// The highway never shows more than this amount of ticks
+const MAX_TICKS: usize = 10 * 25;
+
+/// Lanes from left to right, like in rhythm games
+const LANES: [(Key, &str, Color); 5] = [
+    (Key::Left, "←", Color::Magenta),
+    (Key::Down, "↓", Color::Cyan),
+    (Key::Up, "↑", Color::Green),
+    (Key::Right, "→", Color::Red),
+    (Key::Space, "␣", Color::Yellow),
+];
+const LANE_WIDTH: u16 = 3;
+
+/// Upcoming keys of the solution, falling towards the lane names at the bottom
+pub struct Highway<'a> {
+    pub solution: &'a Solution,
+}
+
+impl Widget for Highway<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title("Next keys");
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        if inner.height == 0 || inner.width < LANE_WIDTH * LANES.len() as u16 - 1 {
+            return;
+        }
+
+        let hit_line = inner.bottom() - 1;
+        let rows = (hit_line - inner.y) as usize;
+
+        for (lane, &(key, name, color)) in LANES.iter().enumerate() {
+            let x = inner.x + lane as u16 * LANE_WIDTH;
+            buf.set_string(
+                x,
+                hit_line,
+                format!("{name} "),
+                Style::new().fg(color).bold(),
+            );
+
+            for row in 0..rows.min(MAX_TICKS / TICKS_PER_ROW) {
+                let ticks = row * TICKS_PER_ROW..(row + 1) * TICKS_PER_ROW;
+                let keys: Vec<_> = ticks.map(|t| self.solution.keys(t)).collect();
+
+                let symbol = if keys.iter().any(|keys| keys.just_pressed(key)) {
+                    Some(("▄▄", Style::new().fg(color).bold()))
+                } else if keys.iter().any(|keys| keys.pressing(key)) {
+                    Some(("██", Style::new().fg(color).dim()))
+                } else {
+                    None
+                };
+
+                if let Some((symbol, style)) = symbol {
+                    buf.set_string(x, hit_line - 1 - row as u16, symbol, style);
+                }
+            }
+        }
+    }
+}
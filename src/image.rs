@@ -23,6 +23,14 @@ pub struct Sprite {
     pub origin: Pos<Origin>,
 }
 
+impl Sprite {
+    /// Dimmed version of the sprite, used to draw ghosts
+    pub fn ghost(mut self) -> Sprite {
+        self.image.color = Color::DarkGray;
+        self
+    }
+}
+
 pub struct SpriteRect {
     pub image: Image,
     pub image_offset: Pos<u16>,
@@ -56,8 +64,10 @@ impl Clip {
     }
 }
 
-impl Shape for SpriteRect {
-    fn draw(&self, painter: &mut ratatui::widgets::canvas::Painter) {
+impl SpriteRect {
+    /// Calls `paint` with the position of every pixel that is set
+    #[allow(clippy::explicit_counter_loop)]
+    pub fn for_each_pixel(&self, mut paint: impl FnMut(u16, u16)) {
         let mut image_y = self.image_offset.y as usize;
 
         for y in self.rect.y.range() {
@@ -65,7 +75,7 @@ impl Shape for SpriteRect {
 
             for x in self.rect.x.range() {
                 if bitmap & 1 == 1 {
-                    painter.paint(x as usize, y as usize, self.image.color);
+                    paint(x, y);
                 }
                 bitmap >>= 1;
             }
@@ -75,6 +85,12 @@ impl Shape for SpriteRect {
     }
 }
 
+impl Shape for SpriteRect {
+    fn draw(&self, painter: &mut ratatui::widgets::canvas::Painter) {
+        self.for_each_pixel(|x, y| painter.paint(x as usize, y as usize, self.image.color));
+    }
+}
+
 impl Sprite {
     fn bounding_box(&self) -> Rect<i32> {
         let Pos { x, y } = self.position;
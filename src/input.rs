@@ -1,6 +1,8 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use std::fmt::{self, Display};
+use std::str::FromStr;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Key {
     Up = 0,
     Down,
@@ -9,7 +11,7 @@ pub enum Key {
     Space,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Keys {
     just_pressed: u8,
     pressing: u8,
@@ -36,6 +38,20 @@ impl Keys {
         self.pressing &= !key.mask();
     }
 
+    /// Holds exactly the keys of `mask`, pressing the ones that were not held before
+    ///
+    /// Each bit of the mask is a [`Key`], starting from the lowest bit in the order of
+    /// [`Key::ALL`].
+    pub fn hold(&mut self, mask: u8) {
+        for key in Key::ALL {
+            match (mask & key.mask() != 0, self.pressing(key)) {
+                (true, false) => self.press(key),
+                (false, true) => self.release(key),
+                _ => {}
+            }
+        }
+    }
+
     pub fn handle_key_event(&mut self, key_event: KeyEvent) {
         let Some(key) = Key::from_code(key_event.code) else {
             return;
@@ -63,6 +79,19 @@ impl Keys {
 }
 
 impl Key {
+    pub const ALL: [Key; 5] = [Key::Up, Key::Down, Key::Left, Key::Right, Key::Space];
+
+    /// Letter used in the text representation of the keys
+    pub fn letter(self) -> char {
+        match self {
+            Key::Up => 'u',
+            Key::Down => 'd',
+            Key::Left => 'l',
+            Key::Right => 'r',
+            Key::Space => 's',
+        }
+    }
+
     fn mask(self) -> u8 {
         1 << self as u8
     }
@@ -90,3 +119,79 @@ impl Key {
         }
     }
 }
+
+/// Written as the letters of the keys being pressed, followed by `+` and the letters of the
+/// keys that have just been pressed, if any. No keys at all is written as `-`.
+///
+/// For example: `ls+s` is holding Left and Space, where Space has just been pressed.
+impl Display for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pressing == 0 && self.just_pressed == 0 {
+            return write!(f, "-");
+        }
+
+        for key in Key::ALL {
+            if self.pressing & key.mask() != 0 {
+                write!(f, "{}", key.letter())?;
+            }
+        }
+
+        if self.just_pressed != 0 {
+            write!(f, "+")?;
+            for key in Key::ALL {
+                if self.just_pressed(key) {
+                    write!(f, "{}", key.letter())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Keys {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Keys, String> {
+        let mut keys = Keys::new();
+        if text == "-" {
+            return Ok(keys);
+        }
+
+        let (pressing, just_pressed) = text.split_once('+').unwrap_or((text, ""));
+
+        for (letters, mask) in [
+            (pressing, &mut keys.pressing),
+            (just_pressed, &mut keys.just_pressed),
+        ] {
+            for letter in letters.chars() {
+                let Some(key) = Key::ALL.into_iter().find(|key| key.letter() == letter) else {
+                    return Err(format!("invalid key '{letter}' in '{text}'"));
+                };
+                *mask |= key.mask();
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys_text_round_trip() {
+        let mut keys = Keys::new();
+        assert_eq!(keys.to_string(), "-");
+
+        keys.press(Key::Left);
+        keys.press(Key::Space);
+        keys.update();
+        keys.press(Key::Space);
+        assert_eq!(keys.to_string(), "ls+s");
+        assert_eq!("ls+s".parse(), Ok(keys));
+        assert_eq!("-".parse(), Ok(Keys::new()));
+        assert!("x".parse::<Keys>().is_err());
+    }
+}
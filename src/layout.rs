@@ -0,0 +1,164 @@
+use crate::math::Size;
+use ratatui::layout::Rect;
+
+/// Layout preferences of a panel, measured in terminal cells
+#[derive(Copy, Clone, Debug)]
+pub struct PanelSpec {
+    /// Preferred width / height ratio
+    pub aspect: f32,
+    pub min_size: Size<u16>,
+}
+
+/// Splits `area` in rows of panels, keeping the order of `panels`.
+///
+/// Every possible split of the panels into consecutive rows is tried, the one where the
+/// panels can show the most area with their preferred aspect ratio wins.
+/// Layouts that do not respect the minimum sizes are only used if there is no other option.
+pub fn pack(area: Rect, panels: &[PanelSpec]) -> Vec<Rect> {
+    if panels.is_empty() {
+        return Vec::new();
+    }
+
+    let mut best: Option<(f32, Vec<Rect>)> = None;
+
+    // Bit `i` of `breaks` set means that a new row starts after panel `i`
+    for breaks in 0..1u32 << (panels.len() - 1) {
+        let rows = split_rows(panels.len(), breaks);
+        let rects = layout_rows(area, panels, &rows);
+        let score = score(panels, &rects);
+
+        if best
+            .as_ref()
+            .is_none_or(|(best_score, _)| score > *best_score)
+        {
+            best = Some((score, rects));
+        }
+    }
+
+    best.map(|(_, rects)| rects).unwrap_or_default()
+}
+
+fn split_rows(panels: usize, breaks: u32) -> Vec<std::ops::Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+
+    for i in 0..panels {
+        if i + 1 == panels || breaks & (1 << i) != 0 {
+            rows.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+
+    rows
+}
+
+fn layout_rows(area: Rect, panels: &[PanelSpec], rows: &[std::ops::Range<usize>]) -> Vec<Rect> {
+    // Height that each row would have if its panels filled the whole width with their aspect
+    let natural_heights: Vec<f32> = rows
+        .iter()
+        .map(|row| {
+            let aspects: f32 = panels[row.clone()].iter().map(|p| p.aspect).sum();
+            area.width as f32 / aspects
+        })
+        .collect();
+
+    let heights = distribute(area.height, &natural_heights);
+
+    let mut rects = Vec::with_capacity(panels.len());
+    let mut y = area.y;
+
+    for (row, height) in rows.iter().zip(heights) {
+        let aspects: Vec<f32> = panels[row.clone()].iter().map(|p| p.aspect).collect();
+        let mut x = area.x;
+
+        for width in distribute(area.width, &aspects) {
+            rects.push(Rect::new(x, y, width, height));
+            x += width;
+        }
+
+        y += height;
+    }
+
+    rects
+}
+
+/// Splits `total` proportionally to `weights`, without leaving any gap
+fn distribute(total: u16, weights: &[f32]) -> Vec<u16> {
+    let weight_sum: f32 = weights.iter().sum();
+    let mut accumulated = 0.;
+    let mut last_end = 0;
+
+    weights
+        .iter()
+        .map(|weight| {
+            accumulated += weight;
+            let end = (total as f32 * accumulated / weight_sum).round() as u16;
+            let size = end.saturating_sub(last_end);
+            last_end = end;
+            size
+        })
+        .collect()
+}
+
+fn score(panels: &[PanelSpec], rects: &[Rect]) -> f32 {
+    let mut useful_area = 0.;
+    let mut missing_cells = 0.;
+
+    for (panel, rect) in panels.iter().zip(rects) {
+        let (width, height) = (rect.width as f32, rect.height as f32);
+
+        // Biggest rectangle with the preferred aspect that fits inside the panel
+        let fit_width = width.min(height * panel.aspect);
+        let fit_height = height.min(width / panel.aspect);
+        useful_area += fit_width * fit_height;
+
+        missing_cells += (panel.min_size.width as f32 - width).max(0.) * height;
+        missing_cells += (panel.min_size.height as f32 - height).max(0.) * width;
+    }
+
+    useful_area - missing_cells * 1000.
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WIDE: PanelSpec = PanelSpec {
+        aspect: 4.,
+        min_size: Size::new(20, 5),
+    };
+    const SQUARE: PanelSpec = PanelSpec {
+        aspect: 1.,
+        min_size: Size::new(10, 10),
+    };
+
+    #[test]
+    fn fills_area_without_overlap() {
+        let area = Rect::new(3, 2, 157, 41);
+        let panels = [WIDE, SQUARE, WIDE, SQUARE, SQUARE];
+
+        let rects = pack(area, &panels);
+        assert_eq!(rects.len(), panels.len());
+
+        let total: u32 = rects.iter().map(|r| r.area() as u32).sum();
+        assert_eq!(total, area.area() as u32);
+
+        for (i, a) in rects.iter().enumerate() {
+            assert_eq!(a.intersection(area), *a);
+            for b in &rects[i + 1..] {
+                assert!(!a.intersects(*b));
+            }
+        }
+    }
+
+    #[test]
+    fn adapts_to_the_terminal_shape() {
+        let panels = [WIDE, WIDE];
+
+        let ultrawide = pack(Rect::new(0, 0, 300, 20), &panels);
+        assert_eq!(ultrawide[0].y, ultrawide[1].y);
+
+        let narrow = pack(Rect::new(0, 0, 80, 40), &panels);
+        assert_eq!(narrow[0].x, narrow[1].x);
+    }
+}
@@ -0,0 +1,32 @@
+//! Terminal arcade where every game is played at the same time
+//!
+//! The games can also be played without a terminal through the [`env`](mod@env) module.
+
+pub mod app;
+mod bot;
+mod cli;
+mod daily;
+pub mod env;
+mod games;
+mod gauntlet;
+mod highway;
+mod image;
+mod input;
+mod layout;
+mod log;
+mod log_panel;
+mod math;
+mod menu;
+mod pixel_canvas;
+mod replay;
+mod run;
+mod run_code;
+mod run_config;
+mod save;
+mod scores;
+mod seed;
+mod settings;
+mod solution;
+mod terminal;
+mod title;
+mod verify;
@@ -1,38 +1,342 @@
-use std::fmt::Arguments;
+use std::collections::VecDeque;
+use std::fmt::{self, Arguments, Display};
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use ratatui::style::Style;
-use ratatui::text::Text;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
 
-pub static LOG: Mutex<Text<'static>> = Mutex::new(Text {
-    lines: vec![],
-    style: Style::new(),
-    alignment: None,
-});
+/// Maximum amount of records kept in memory, the oldest ones are dropped first.
+const LOG_CAPACITY: usize = 1 << 10;
 
-pub fn log_format(args: Arguments<'_>) {
-    if let Some(text) = args.as_str() {
-        let mut log = LOG.lock().unwrap();
-        for line in text.lines() {
-            log.push_line(line.to_string());
+/// Environment variable with the path of the log file
+pub const LOG_FILE_ENV: &str = "MINITERMS_LOG_FILE";
+/// Environment variable with the name of the lowest [`Level`] that is logged
+pub const LOG_LEVEL_ENV: &str = "MINITERMS_LOG_LEVEL";
+
+/// Targets of the records emitted by this crate start with this prefix, it is not displayed
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+pub static LOG: Mutex<Log> = Mutex::new(Log::new(LOG_CAPACITY));
+
+/// Routes the records of the `log` crate facade into [`LOG`]
+static FACADE: Facade = Facade;
+
+#[allow(unused)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+pub struct Record {
+    pub level: Level,
+    /// Module that emitted the record, without the crate prefix
+    pub target: String,
+    pub tick: usize,
+    pub time: Duration,
+    pub message: String,
+    /// How many times this exact message has been logged in a row
+    pub repeat: usize,
+}
+
+pub struct Log {
+    records: VecDeque<Record>,
+    capacity: usize,
+    min_level: Level,
+    tick: usize,
+    start: Option<Instant>,
+    file: Option<LineWriter<File>>,
+}
+
+struct Facade;
+
+impl Level {
+    pub const ALL: [Level; 5] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Level> {
+        Level::ALL
+            .into_iter()
+            .find(|level| level.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Level::Trace => Color::DarkGray,
+            Level::Debug => Color::Blue,
+            Level::Info => Color::Green,
+            Level::Warn => Color::Yellow,
+            Level::Error => Color::Red,
+        }
+    }
+}
+
+impl Log {
+    pub const fn new(capacity: usize) -> Log {
+        Log {
+            records: VecDeque::new(),
+            capacity,
+            min_level: Level::Trace,
+            tick: 0,
+            start: None,
+            file: None,
+        }
+    }
+
+    /// Appends every new record to the file at `path`
+    pub fn open_file(&mut self, path: &Path) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.file = Some(LineWriter::new(file));
+        Ok(())
+    }
+
+    pub fn push(&mut self, level: Level, target: &str, message: String) {
+        if level < self.min_level {
+            return;
+        }
+
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        let time = self.start.get_or_insert_with(Instant::now).elapsed();
+
+        // Rate limit repeated lines by merging them into the previous record
+        if let Some(last) = self.records.back_mut() {
+            if last.level == level && last.target == target && last.message == message {
+                last.repeat += 1;
+                last.tick = self.tick;
+                last.time = time;
+                return;
+            }
+        }
+
+        let last_repeat = self.records.back().map_or(1, |last| last.repeat);
+        if last_repeat > 1 {
+            self.write_file(format_args!("  last message repeated {last_repeat} times"));
+        }
+
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+
+        let record = Record {
+            level,
+            target: target.to_string(),
+            tick: self.tick,
+            time,
+            message,
+            repeat: 1,
+        };
+        self.write_file(format_args!("{record}"));
+        self.records.push_back(record);
+    }
+
+    fn write_file(&mut self, line: Arguments) {
+        if let Some(file) = &mut self.file {
+            if writeln!(file, "{line}").is_err() {
+                // Do not keep failing on every record, e.g. when the disk is full
+                self.file = None;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &Record> + '_ {
+        self.records.iter()
+    }
+
+    pub fn set_min_level(&mut self, level: Level) {
+        self.min_level = level;
+    }
+}
+
+impl Record {
+    pub fn lines(&self) -> impl Iterator<Item = Line<'static>> + '_ {
+        let header = Span::styled(
+            format!("{:>7.2} #{:<5} ", self.time.as_secs_f32(), self.tick),
+            Style::new().dark_gray(),
+        );
+        let level = Span::styled(
+            format!("{:<5} ", self.level.name()),
+            Style::new().fg(self.level.color()),
+        );
+        let target = Span::styled(format!("{}: ", self.target), Style::new().dark_gray());
+        let repeat = (self.repeat > 1).then(|| Span::raw(format!(" (x{})", self.repeat)));
+
+        let mut message = self.message.lines();
+        let first_line = message.next().unwrap_or_default().to_string();
+
+        let mut first = Line::from(vec![header, level, target, Span::raw(first_line)]);
+        if let Some(repeat) = repeat {
+            first.spans.push(repeat);
+        }
+
+        std::iter::once(first).chain(message.map(|line| Line::raw(format!("  {line}"))))
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.3} #{} {} {}: {}",
+            self.time.as_secs_f32(),
+            self.tick,
+            self.level.name(),
+            self.target,
+            self.message,
+        )
+    }
+}
+
+impl From<::log::Level> for Level {
+    fn from(level: ::log::Level) -> Self {
+        match level {
+            ::log::Level::Trace => Level::Trace,
+            ::log::Level::Debug => Level::Debug,
+            ::log::Level::Info => Level::Info,
+            ::log::Level::Warn => Level::Warn,
+            ::log::Level::Error => Level::Error,
         }
-    } else {
-        let text = args.to_string();
-        let mut log = LOG.lock().unwrap();
-        for line in text.lines() {
-            log.push_line(line.to_string());
+    }
+}
+
+impl ::log::Log for Facade {
+    fn enabled(&self, metadata: &::log::Metadata) -> bool {
+        Level::from(metadata.level()) >= LOG.lock().unwrap().min_level
+    }
+
+    fn log(&self, record: &::log::Record) {
+        log_format(record.level().into(), record.target(), *record.args());
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &mut LOG.lock().unwrap().file {
+            let _ = file.flush();
         }
     }
 }
 
+/// Installs the `log` crate facade and, if [`LOG_FILE_ENV`] or `log_file` is set, the file sink.
+///
+/// Every level is logged, unless [`LOG_LEVEL_ENV`] names a higher one.
+pub fn init(log_file: Option<&Path>) -> io::Result<()> {
+    // Only fails if a logger has already been set
+    if ::log::set_logger(&FACADE).is_ok() {
+        ::log::set_max_level(::log::LevelFilter::Trace);
+    }
+
+    if let Some(name) = std::env::var_os(LOG_LEVEL_ENV) {
+        let name = name.to_string_lossy();
+        let level = Level::from_name(&name).ok_or_else(|| {
+            let message = format!("unknown log level '{name}' in {LOG_LEVEL_ENV}");
+            io::Error::new(io::ErrorKind::InvalidInput, message)
+        })?;
+        LOG.lock().unwrap().set_min_level(level);
+    }
+
+    let env_file = std::env::var_os(LOG_FILE_ENV);
+    if let Some(path) = log_file.or(env_file.as_deref().map(Path::new)) {
+        LOG.lock().unwrap().open_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Advances the tick number that is attached to every new record.
+pub fn next_tick() {
+    LOG.lock().unwrap().tick += 1;
+}
+
+pub fn log_format(level: Level, target: &str, args: Arguments<'_>) {
+    let message = match args.as_str() {
+        Some(text) => text.to_string(),
+        None => args.to_string(),
+    };
+
+    LOG.lock().unwrap().push(level, target, message);
+}
+
 #[allow(unused)]
 macro_rules! log {
-    () => {
-        $crate::log::log_format(format_args!(""));
-    };
-    ($($arg:tt)*) => {{
-        $crate::log::log_format(format_args!($($arg)*));
+    ($level:expr, $($arg:tt)+) => {{
+        $crate::log::log_format($level, module_path!(), format_args!($($arg)+));
     }};
 }
 
-pub(crate) use log;
+#[allow(unused)]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[allow(unused)]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[allow(unused)]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[allow(unused)]
+macro_rules! warning {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[allow(unused)]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[allow(unused)]
+pub(crate) use {debug, error, info, log, trace, warning};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ring_buffer_and_repeats() {
+        let mut log = Log::new(2);
+        log.set_min_level(Level::Debug);
+
+        log.push(Level::Info, "miniterms::a", "first".into());
+        log.push(Level::Info, "miniterms::a", "second".into());
+        log.push(Level::Info, "miniterms::a", "second".into());
+        log.push(Level::Trace, "miniterms::a", "filtered".into());
+
+        let records: Vec<_> = log.records().collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].repeat, 2);
+        assert_eq!(records[1].target, "a");
+
+        log.push(Level::Warn, "miniterms::b", "third".into());
+
+        let messages: Vec<_> = log.records().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["second", "third"]);
+    }
+}
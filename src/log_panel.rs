@@ -0,0 +1,162 @@
+use crate::log::{Level, LOG};
+use crate::math::Size;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    style::{Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Paragraph, Widget},
+};
+
+const LOG_PANEL_WIDTH: u16 = 40;
+
+pub struct LogPanel {
+    visible: bool,
+    focused: bool,
+    /// Is the text filter being edited
+    editing_filter: bool,
+    filter: String,
+    min_level: Level,
+    /// First visible line, `None` while following the end of the log
+    scroll: Option<usize>,
+
+    // Values of the last render, used to scroll by pages
+    last_top: usize,
+    last_height: usize,
+    last_len: usize,
+}
+
+impl LogPanel {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            focused: false,
+            editing_filter: false,
+            filter: String::new(),
+            min_level: Level::Trace,
+            scroll: None,
+            last_top: 0,
+            last_height: 0,
+            last_len: 0,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        let is_empty = LOG.lock().unwrap().is_empty();
+
+        if self.visible && (self.focused || !is_empty) {
+            LOG_PANEL_WIDTH
+        } else {
+            0
+        }
+    }
+
+    /// Returns true while the panel takes the keys before the rest of the app
+    pub fn is_focused(&self) -> bool {
+        self.visible && (self.focused || self.editing_filter)
+    }
+
+    /// Returns true if the key has been consumed by the panel
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        if key_event.kind == KeyEventKind::Release {
+            return self.focused;
+        }
+
+        if key_event.code == KeyCode::F(2) {
+            self.visible = !self.visible;
+            self.focused &= self.visible;
+            return true;
+        }
+
+        if !self.visible {
+            return false;
+        }
+
+        if self.editing_filter {
+            match key_event.code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Enter | KeyCode::Esc => self.editing_filter = false,
+                _ => {}
+            }
+            return true;
+        }
+
+        match key_event.code {
+            KeyCode::Tab => self.focused = !self.focused,
+            KeyCode::PageUp => self.scroll_up(self.last_height.max(1)),
+            KeyCode::PageDown => self.scroll_down(self.last_height.max(1)),
+            KeyCode::Home => self.scroll = Some(0),
+            KeyCode::End => self.scroll = None,
+            _ if !self.focused => return false,
+
+            KeyCode::Esc => self.focused = false,
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_down(1),
+            KeyCode::Char('/') => {
+                self.editing_filter = true;
+                self.filter.clear();
+            }
+            KeyCode::Char('t') => self.min_level = Level::Trace,
+            KeyCode::Char('d') => self.min_level = Level::Debug,
+            KeyCode::Char('i') => self.min_level = Level::Info,
+            KeyCode::Char('w') => self.min_level = Level::Warn,
+            KeyCode::Char('e') => self.min_level = Level::Error,
+            _ => {}
+        }
+
+        true
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        self.scroll = Some(self.last_top.saturating_sub(lines));
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        let top = self.last_top + lines;
+        let max_top = self.last_len.saturating_sub(self.last_height);
+
+        self.scroll = (top < max_top).then_some(top);
+    }
+
+    pub fn widget(&mut self, area: Size<u16>) -> impl Widget {
+        let log = LOG.lock().unwrap();
+
+        let lines: Vec<Line> = log
+            .records()
+            .filter(|record| record.level >= self.min_level)
+            .filter(|record| self.filter.is_empty() || record.message.contains(&self.filter))
+            .flat_map(|record| record.lines())
+            .collect();
+
+        let height = area.height.saturating_sub(2) as usize;
+        let max_top = lines.len().saturating_sub(height);
+        let top = self.scroll.unwrap_or(max_top).min(max_top);
+
+        self.last_top = top;
+        self.last_height = height;
+        self.last_len = lines.len();
+
+        Paragraph::new(Text::from(lines))
+            .block(self.block())
+            .scroll((top as u16, 0))
+    }
+
+    fn block(&self) -> Block<'static> {
+        let mut title = format!("Log {}+", self.min_level.name());
+        if self.editing_filter || !self.filter.is_empty() {
+            title += &format!(" /{}", self.filter);
+        }
+        if self.scroll.is_some() {
+            title += " (paused)";
+        }
+
+        let block = Block::bordered().title(title);
+        if self.focused {
+            block.border_style(Style::new().yellow())
+        } else {
+            block
+        }
+    }
+}
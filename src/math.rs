@@ -1,13 +1,13 @@
 use num_traits::Num;
 use std::ops::{Add, Range, Sub};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Pos<T: Copy> {
     pub x: T,
     pub y: T,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Size<T: Copy> {
     pub width: T,
     pub height: T,
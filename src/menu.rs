@@ -0,0 +1,250 @@
+use crate::settings::Settings;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+use std::time::Duration;
+
+/// Seconds of the countdown shown before resuming
+const COUNTDOWN_START: u64 = 3;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MenuAction {
+    None,
+    Restart,
+    /// Save the run to resume it later, and quit
+    Save,
+    Quit,
+}
+
+/// Options of the main page, in the order they are shown
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MenuOption {
+    Resume,
+    Restart,
+    Settings,
+    SaveAndQuit,
+    Quit,
+}
+
+impl MenuOption {
+    const ALL: [MenuOption; 5] = [
+        MenuOption::Resume,
+        MenuOption::Restart,
+        MenuOption::Settings,
+        MenuOption::SaveAndQuit,
+        MenuOption::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuOption::Resume => "Resume",
+            MenuOption::Restart => "Restart",
+            MenuOption::Settings => "Settings",
+            MenuOption::SaveAndQuit => "Save & Quit",
+            MenuOption::Quit => "Quit",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Page {
+    Closed,
+    Main,
+    Settings,
+    /// Ticks left until the game is resumed
+    Countdown(usize),
+}
+
+pub struct PauseMenu {
+    page: Page,
+    selected: usize,
+    /// Duration of each tick of the countdown
+    tick_rate: Duration,
+}
+
+impl PauseMenu {
+    pub fn new(tick_rate: Duration) -> Self {
+        Self {
+            page: Page::Closed,
+            selected: 0,
+            tick_rate,
+        }
+    }
+
+    /// Returns true while the simulation must be frozen
+    pub fn is_paused(&self) -> bool {
+        self.page != Page::Closed
+    }
+
+    /// Returns true while the options are shown
+    pub fn is_open(&self) -> bool {
+        matches!(self.page, Page::Main | Page::Settings)
+    }
+
+    pub fn open(&mut self) {
+        if matches!(self.page, Page::Closed | Page::Countdown(_)) {
+            self.page = Page::Main;
+            self.selected = 0;
+        }
+    }
+
+    fn resume(&mut self) {
+        let ticks = Duration::from_secs(COUNTDOWN_START).div_duration_f64(self.tick_rate);
+        self.page = Page::Countdown((ticks.ceil() as usize).max(1));
+    }
+
+    /// Advances the resume countdown, must be called once per tick
+    pub fn update(&mut self) {
+        if let Page::Countdown(ticks) = &mut self.page {
+            *ticks -= 1;
+            if *ticks == 0 {
+                self.page = Page::Closed;
+            }
+        }
+    }
+
+    /// Keys are only consumed while the menu is open
+    pub fn handle_key(
+        &mut self,
+        key_event: KeyEvent,
+        settings: &mut Settings,
+    ) -> Option<MenuAction> {
+        if key_event.kind == KeyEventKind::Release {
+            return self.is_paused().then_some(MenuAction::None);
+        }
+
+        let options = match self.page {
+            Page::Closed | Page::Countdown(_) => {
+                if let KeyCode::Esc | KeyCode::Char('q') = key_event.code {
+                    self.open();
+                    return Some(MenuAction::None);
+                }
+                return None;
+            }
+            Page::Main => MenuOption::ALL.len(),
+            Page::Settings => settings.items().len() + 1,
+        };
+
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('w') => {
+                self.selected = (self.selected + options - 1) % options;
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Char('s') => {
+                self.selected = (self.selected + 1) % options;
+            }
+            KeyCode::Esc if self.page == Page::Settings => self.open_main(MenuOption::Settings),
+            KeyCode::Esc => self.resume(),
+            KeyCode::Enter | KeyCode::Char(' ') => return Some(self.select(settings)),
+            _ => {}
+        }
+
+        Some(MenuAction::None)
+    }
+
+    fn open_main(&mut self, selected: MenuOption) {
+        self.page = Page::Main;
+        self.selected = selected as usize;
+    }
+
+    fn select(&mut self, settings: &mut Settings) -> MenuAction {
+        match self.page {
+            Page::Main => match MenuOption::ALL[self.selected] {
+                MenuOption::Resume => self.resume(),
+                MenuOption::Restart => {
+                    self.resume();
+                    return MenuAction::Restart;
+                }
+                MenuOption::Settings => {
+                    self.page = Page::Settings;
+                    self.selected = 0;
+                }
+                MenuOption::SaveAndQuit => {
+                    self.page = Page::Closed;
+                    return MenuAction::Save;
+                }
+                MenuOption::Quit => {
+                    self.page = Page::Closed;
+                    return MenuAction::Quit;
+                }
+            },
+            Page::Settings if self.selected == settings.items().len() => {
+                self.open_main(MenuOption::Settings)
+            }
+            Page::Settings => settings.toggle(self.selected),
+            Page::Closed | Page::Countdown(_) => {}
+        }
+
+        MenuAction::None
+    }
+
+    /// Draws the menu, `run_code` is shown so the run can be shared
+    pub fn render(&self, frame: &mut Frame, settings: &Settings, run_code: &str) {
+        let lines: Vec<Line> = match self.page {
+            Page::Closed => return,
+            Page::Countdown(ticks) => {
+                let number = self.tick_rate.mul_f64(ticks as f64).as_secs_f64().ceil();
+                vec![Line::from(number.to_string()).bold()]
+            }
+            Page::Main => MenuOption::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, option)| self.option_line(i, option.label().to_string()))
+                .collect(),
+            Page::Settings => settings
+                .items()
+                .into_iter()
+                .map(|item| format!("{:<20} {:>4}", item.name, item.value))
+                .chain(std::iter::once("Back".to_string()))
+                .enumerate()
+                .map(|(i, option)| self.option_line(i, option))
+                .collect(),
+        };
+
+        let title = match self.page {
+            Page::Settings => "Settings",
+            _ => "Paused",
+        };
+
+        let mut block = Block::bordered().title(title);
+        let mut width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 6;
+        if self.page == Page::Main {
+            block = block.title_bottom(Line::from(run_code).centered());
+            width = width.max(run_code.len() as u16 + 4);
+        }
+
+        let area = centered(frame.size(), width.max(12), lines.len() as u16 + 2);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(Text::from(lines))
+                .alignment(Alignment::Center)
+                .block(block),
+            area,
+        );
+    }
+
+    fn option_line(&self, index: usize, option: String) -> Line<'static> {
+        if index == self.selected {
+            Line::styled(format!("> {option} <"), Style::new().yellow())
+        } else {
+            Line::from(option)
+        }
+    }
+}
+
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
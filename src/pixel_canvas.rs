@@ -9,10 +9,77 @@ pub struct PixelCanvas<'a, 'b> {
     pub size: Size<u16>,
 }
 
-impl<'a, 'b> PixelCanvas<'a, 'b> {
-    pub fn draw(&mut self, sprite: Sprite) {
+/// Surface where the games draw their sprites
+pub trait DrawTarget {
+    fn draw(&mut self, sprite: Sprite);
+}
+
+impl<'a, 'b> DrawTarget for PixelCanvas<'a, 'b> {
+    fn draw(&mut self, sprite: Sprite) {
         if let Some(rect) = sprite.rect(self.origin, self.size) {
             self.ctx.draw(&rect);
         }
     }
 }
+
+/// Pixels drawn without a terminal, one bit per pixel and without colors
+///
+/// The rows are stored from top to bottom, each one packed in `width.div_ceil(8)` bytes with
+/// the leftmost pixel in the lowest bit of the first byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bitmap {
+    origin: Pos<i32>,
+    size: Size<u16>,
+    bytes: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn new(origin: Pos<i32>, size: Size<u16>) -> Bitmap {
+        let row_bytes = size.width.div_ceil(8) as usize;
+        Bitmap {
+            origin,
+            size,
+            bytes: vec![0; row_bytes * size.height as usize],
+        }
+    }
+
+    pub fn size(&self) -> Size<u16> {
+        self.size
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> bool {
+        let Some((index, bit)) = bit_index(self.size, x, y) else {
+            return false;
+        };
+        self.bytes[index] & bit != 0
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Byte and bit of the pixel at `x`, `y` in a bitmap of `size`
+fn bit_index(size: Size<u16>, x: u16, y: u16) -> Option<(usize, u8)> {
+    if x >= size.width || y >= size.height {
+        return None;
+    }
+
+    let row_bytes = size.width.div_ceil(8) as usize;
+    Some((y as usize * row_bytes + x as usize / 8, 1 << (x % 8)))
+}
+
+impl DrawTarget for Bitmap {
+    fn draw(&mut self, sprite: Sprite) {
+        let Some(rect) = sprite.rect(self.origin, self.size) else {
+            return;
+        };
+
+        let size = self.size;
+        rect.for_each_pixel(|x, y| {
+            if let Some((index, bit)) = bit_index(size, x, y) {
+                self.bytes[index] |= bit;
+            }
+        });
+    }
+}
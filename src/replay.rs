@@ -0,0 +1,209 @@
+use crate::games::GameKind;
+use crate::input::Keys;
+use crate::math::Size;
+use crate::run::REWIND_TICKS;
+use crate::run_config::RunConfig;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+
+const REPLAY_HEADER: &str = "# miniterms replay v1";
+
+/// Games simulated during a tick, with the size of their panels
+pub type Panels = Vec<(GameKind, Size<u16>)>;
+
+pub struct ReplayTick {
+    pub keys: Keys,
+    pub panels: Panels,
+    /// The tick goes back to the previous one instead of simulating
+    pub rewind: bool,
+    /// Hash of the state at the end of the tick, if it was recorded
+    pub hash: Option<u64>,
+}
+
+/// Player input of a run, along with the panel sizes that the games had
+///
+/// File format: the header, the config as `# key value` lines, then one line per tick with
+/// the [`Keys`] text representation. Every time the panels change, a line like
+/// `@ trex 120x30 tetris 22x22` is written before the keys of the tick. Ticks spent rewinding
+/// are written as `<`. Each tick can be followed by a line like `= 0123456789abcdef`, the hash
+/// of the state at the end of the tick.
+pub struct Replay {
+    pub config: RunConfig,
+    ticks: VecDeque<ReplayTick>,
+}
+
+/// Writes the player input to a replay file while playing
+pub struct Recorder {
+    file: LineWriter<File>,
+    panels: Option<Panels>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        Self::read(BufReader::new(File::open(path)?), path, 0)
+    }
+
+    /// Reads a replay that starts at line `first_line` of the file at `path`
+    pub fn read(reader: impl BufRead, path: &Path, first_line: usize) -> io::Result<Replay> {
+        let invalid = |line: usize, message: String| {
+            let message = format!("{}:{}: {message}", path.display(), line + 1);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (first_line + number, line));
+
+        match lines.next() {
+            Some((_, Ok(header))) if header == REPLAY_HEADER => {}
+            Some((_, Err(error))) => return Err(error),
+            _ => return Err(invalid(first_line, "not a miniterms replay".to_string())),
+        }
+
+        let mut replay = Replay {
+            config: RunConfig::new(),
+            ticks: VecDeque::new(),
+        };
+        let mut panels = Panels::new();
+
+        for (number, line) in lines {
+            let line = line?;
+
+            if let Some(field) = line.strip_prefix("# ") {
+                let (key, value) = field.split_once(' ').unwrap_or((field, ""));
+                replay
+                    .config
+                    .set_field(key, value)
+                    .map_err(|error| invalid(number, error))?;
+            } else if let Some(line) = line.strip_prefix('@') {
+                panels = parse_panels(line).map_err(|error| invalid(number, error))?;
+            } else if let Some(hash) = line.strip_prefix('=') {
+                let hash = u64::from_str_radix(hash.trim(), 16)
+                    .map_err(|_| invalid(number, format!("invalid hash '{}'", hash.trim())))?;
+                let tick = replay.ticks.back_mut();
+                let tick = tick.ok_or(invalid(number, "hash before any tick".to_string()))?;
+                tick.hash = Some(hash);
+            } else if line.trim() == "<" {
+                replay.ticks.push_back(ReplayTick {
+                    keys: Keys::new(),
+                    panels: panels.clone(),
+                    rewind: true,
+                    hash: None,
+                });
+            } else {
+                let keys = line
+                    .trim()
+                    .parse()
+                    .map_err(|error| invalid(number, error))?;
+                replay.ticks.push_back(ReplayTick {
+                    keys,
+                    panels: panels.clone(),
+                    rewind: false,
+                    hash: None,
+                });
+            }
+        }
+
+        Ok(replay)
+    }
+
+    /// Input of the next tick, `None` once the replay has finished
+    pub fn next(&mut self) -> Option<ReplayTick> {
+        self.ticks.pop_front()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// Keys of the remaining ticks that were not undone by a rewind
+    ///
+    /// Each rewind undoes the previous tick, as long as it is within the last [`REWIND_TICKS`],
+    /// like [`Run`](crate::run::Run) does.
+    pub fn into_keys(self) -> impl Iterator<Item = Keys> {
+        let mut keys = Vec::new();
+        // Ticks that can still be rewound
+        let mut history = 0;
+
+        for tick in self.ticks {
+            if !tick.rewind {
+                keys.push(tick.keys);
+                history = (history + 1).min(REWIND_TICKS);
+            } else if history > 0 {
+                keys.pop();
+                history -= 1;
+            }
+        }
+
+        keys.into_iter()
+    }
+}
+
+/// Reads the panels of a `@` line, without the `@`
+pub fn parse_panels(text: &str) -> Result<Panels, String> {
+    let mut words = text.split_whitespace();
+    let mut panels = Panels::new();
+
+    while let Some(id) = words.next() {
+        let game = parse_game(id)?;
+
+        let size = words.next().ok_or(format!("missing size of '{id}'"))?;
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or(format!("invalid size '{size}'"))?;
+
+        panels.push((game, Size::new(width, height)));
+    }
+
+    Ok(panels)
+}
+
+/// Reads space separated [`GameKind::id`]
+pub fn parse_games(text: &str) -> Result<Vec<GameKind>, String> {
+    text.split_whitespace().map(parse_game).collect()
+}
+
+fn parse_game(id: &str) -> Result<GameKind, String> {
+    GameKind::from_id(id).ok_or(format!("unknown game '{id}'"))
+}
+
+impl Recorder {
+    /// Starts a replay file for a run of `config`, which must not have started yet
+    pub fn create(path: &Path, config: &RunConfig) -> io::Result<Recorder> {
+        let mut file = LineWriter::new(File::create(path)?);
+
+        writeln!(file, "{REPLAY_HEADER}")?;
+        for (key, value) in config.fields() {
+            writeln!(file, "# {key} {value}")?;
+        }
+
+        Ok(Recorder { file, panels: None })
+    }
+
+    pub fn record(&mut self, panels: &Panels, keys: Keys) -> io::Result<()> {
+        if self.panels.as_ref() != Some(panels) {
+            write!(self.file, "@")?;
+            for (game, size) in panels {
+                write!(self.file, " {} {}x{}", game.id(), size.width, size.height)?;
+            }
+            writeln!(self.file)?;
+
+            self.panels = Some(panels.clone());
+        }
+
+        writeln!(self.file, "{keys}")
+    }
+
+    pub fn record_rewind(&mut self) -> io::Result<()> {
+        writeln!(self.file, "<")
+    }
+
+    /// Records the hash of the state at the end of the tick
+    pub fn record_hash(&mut self, hash: u64) -> io::Result<()> {
+        writeln!(self.file, "= {hash:016x}")
+    }
+}
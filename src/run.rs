@@ -0,0 +1,687 @@
+use crate::games::{trex::TRexGame, GameContext, GameKind};
+use crate::gauntlet::Gauntlet;
+use crate::highway::{Highway, HIGHWAY_WIDTH};
+use crate::input::Keys;
+use crate::layout;
+use crate::log::{info, warning};
+use crate::math::Size;
+use crate::pixel_canvas::PixelCanvas;
+use crate::replay::{self, Panels, Recorder, Replay};
+use crate::run_config::{RunConfig, RunMode};
+use crate::save::{StateReader, StateWriter};
+use crate::seed::{Seed, StableHasher};
+use crate::settings::Settings;
+use crate::solution::{self, PlannedGame, Solution, SolutionRecorder};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Stylize},
+    symbols::Marker,
+    terminal::Frame,
+    widgets::{
+        canvas::{Canvas, Map, MapResolution},
+        Block, Paragraph, Widget,
+    },
+};
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+/// Height of the minimised games while another game is zoomed
+const ZOOM_STRIP_HEIGHT: u16 = 8;
+
+/// Ticks that can be rewound
+pub const REWIND_TICKS: usize = 5 * 25;
+/// Ticks rewound after each press of the rewind key, enough to last until it is repeated
+const REWIND_HOLD_TICKS: usize = 8;
+
+/// State of the games being played
+pub struct Run {
+    config: RunConfig,
+    seed: Seed,
+    /// Games being played, in the same order as in the config
+    active: Vec<GameKind>,
+    gauntlet: Option<Gauntlet>,
+    trex: TRexGame,
+    keys: Keys,
+    solution: Solution,
+    ticks: usize,
+    /// Game shown using all the space
+    zoom: Option<GameKind>,
+    /// Input played back instead of the keyboard, until it finishes
+    replay: Option<Replay>,
+    recorder: Option<Recorder>,
+    solution_recorder: Option<SolutionRecorder>,
+    /// Panels the solution has been planned for
+    planned: Panels,
+    /// Snapshots of the last ticks, the newest at the back
+    history: VecDeque<Snapshot>,
+    /// Ticks left to rewind since the rewind key was last pressed
+    rewinding: usize,
+    /// First tick where the state did not match the one of the replay
+    diverged: Option<usize>,
+}
+
+/// State of the simulation at the start of a tick
+#[derive(Clone)]
+struct Snapshot {
+    active: Vec<GameKind>,
+    gauntlet: Option<Gauntlet>,
+    trex: TRexGame,
+    solution: Solution,
+    ticks: usize,
+    planned: Panels,
+}
+
+impl Run {
+    pub fn new(config: RunConfig) -> Self {
+        let (gauntlet, active) = match config.mode {
+            RunMode::Classic => (None, config.games.clone()),
+            RunMode::Gauntlet => {
+                let (gauntlet, active) = Gauntlet::new(&config.games, config.unlock);
+                (Some(gauntlet), active)
+            }
+        };
+
+        let seed = Seed::from_text(&config.seed);
+        let solution = Solution::new(solution::strategy(
+            &config.solution,
+            &seed,
+            config.difficulty,
+        ));
+
+        Self {
+            config,
+            seed,
+            active,
+            gauntlet,
+            trex: TRexGame::new(&seed),
+            keys: Keys::new(),
+            solution,
+            ticks: 0,
+            zoom: None,
+            replay: None,
+            recorder: None,
+            solution_recorder: None,
+            planned: Panels::new(),
+            history: VecDeque::new(),
+            rewinding: 0,
+            diverged: None,
+        }
+    }
+
+    /// Run that plays back the input of `replay`
+    pub fn from_replay(replay: Replay) -> Self {
+        let mut run = Run::new(replay.config.clone());
+        if !replay.is_finished() {
+            run.replay = Some(replay);
+        }
+        run
+    }
+
+    /// Writes the input of the run to `recorder` from now on
+    pub fn record(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Writes the keys of the solution to `recorder` from now on
+    pub fn record_solution(&mut self, recorder: SolutionRecorder) {
+        self.solution_recorder = Some(recorder);
+    }
+
+    /// Writes the state of the simulation, without the history of the rewind
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.line("ticks").value(self.ticks);
+        state.line("active");
+        for game in &self.active {
+            state.value(game.id());
+        }
+        state.line("keys").value(self.keys);
+        state.line("planned");
+        for (game, size) in &self.planned {
+            state
+                .value(game.id())
+                .value(format!("{}x{}", size.width, size.height));
+        }
+
+        if let Some(gauntlet) = &self.gauntlet {
+            gauntlet.save_state(state);
+        }
+        self.trex.save_state(state);
+        self.solution.save_state(state);
+    }
+
+    /// Run of `config` in the state written by [`Run::save_state`]
+    pub fn load_state(config: RunConfig, state: &mut StateReader) -> Result<Run, String> {
+        let mut run = Run::new(config);
+
+        state.line("ticks")?;
+        run.ticks = state.value()?;
+        state.line("active")?;
+        run.active = replay::parse_games(state.rest())?;
+        state.line("keys")?;
+        run.keys = state.value()?;
+        state.line("planned")?;
+        run.planned = replay::parse_panels(state.rest())?;
+
+        if let Some(gauntlet) = &mut run.gauntlet {
+            gauntlet.load_state(state)?;
+        }
+        run.trex.load_state(state)?;
+
+        // The planner simulates the games from their current state
+        let games = run.planned_games(&run.planned);
+        run.solution.load_state(state, games)?;
+
+        Ok(run)
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn config(&self) -> &RunConfig {
+        &self.config
+    }
+
+    /// Ticks simulated since the start of the run
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    pub fn score(&self) -> usize {
+        self.active
+            .iter()
+            .map(|game| match game {
+                GameKind::TRex => self.trex.score(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Whether the player of `game` is hitting something
+    pub fn is_colliding(&self, game: GameKind) -> bool {
+        match game {
+            GameKind::TRex => self.trex.is_colliding(),
+            _ => false,
+        }
+    }
+
+    /// Adds a game to the run, starting from its initial state
+    fn join(&mut self, game: GameKind) {
+        if game == GameKind::TRex {
+            self.trex = TRexGame::new(&self.seed);
+        }
+
+        self.active.push(game);
+        let order = |game: &GameKind| self.config.games.iter().position(|g| g == game);
+        self.active.sort_by_key(order);
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.is_replaying() {
+            return;
+        }
+
+        if key_event.code == KeyCode::Backspace {
+            self.rewinding = match key_event.kind {
+                KeyEventKind::Press | KeyEventKind::Repeat => REWIND_HOLD_TICKS,
+                KeyEventKind::Release => 0,
+            };
+        }
+        self.keys.handle_key_event(key_event);
+    }
+
+    /// Holds exactly the keys of `mask` from now on, see [`Keys::hold`]
+    pub fn hold_keys(&mut self, mask: u8) {
+        if !self.is_replaying() {
+            self.keys.hold(mask);
+        }
+    }
+
+    /// Zooms the game at `index` of the run, or goes back to all the games if it is already zoomed
+    pub fn toggle_zoom(&mut self, index: usize) {
+        let Some(&game) = self.active.get(index) else {
+            return;
+        };
+
+        self.zoom = if self.zoom == Some(game) {
+            None
+        } else {
+            Some(game)
+        };
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom.is_some()
+    }
+
+    /// Splits `area` into the games area and the highway, if it is shown
+    fn split_highway(area: Rect, settings: &Settings) -> (Rect, Option<Rect>) {
+        use Constraint::*;
+
+        if !settings.show_highway {
+            return (area, None);
+        }
+
+        let [games, highway] = Layout::horizontal([Fill(1), Length(HIGHWAY_WIDTH)]).areas(area);
+        (games, Some(highway))
+    }
+
+    /// Games to show in `area` with their panel, the games that are not shown are paused
+    fn panels(&self, area: Rect, settings: &Settings) -> Vec<(GameKind, Rect)> {
+        let (area, _) = Self::split_highway(area, settings);
+        let games = self.active.clone();
+
+        match self.zoom {
+            None => {
+                let specs: Vec<_> = games.iter().map(|game| game.panel_spec()).collect();
+                let areas = layout::pack(area, &specs);
+                games.into_iter().zip(areas).collect()
+            }
+            Some(zoomed) if settings.zoom_strip => {
+                use Constraint::*;
+
+                let others: Vec<_> = games.into_iter().filter(|&g| g != zoomed).collect();
+                let strip_height = if others.is_empty() {
+                    0
+                } else {
+                    ZOOM_STRIP_HEIGHT
+                };
+
+                let [zoom_area, strip] =
+                    Layout::vertical([Fill(1), Length(strip_height)]).areas(area);
+                let strip_areas = Layout::horizontal(vec![Fill(1); others.len()]).split(strip);
+
+                let strip_panels = others.into_iter().zip(strip_areas.iter().copied());
+                [(zoomed, zoom_area)]
+                    .into_iter()
+                    .chain(strip_panels)
+                    .collect()
+            }
+            Some(zoomed) => vec![(zoomed, area)],
+        }
+    }
+
+    /// Simulates a tick of the games shown in `area`
+    ///
+    /// While replaying, the keys and the panel sizes come from the replay instead.
+    pub fn update(&mut self, area: Rect, settings: &Settings) {
+        let (panels, rewind, expected_hash) = match self.replay.as_mut().and_then(Replay::next) {
+            Some(tick) => {
+                self.keys = tick.keys;
+                (tick.panels, tick.rewind, tick.hash)
+            }
+            None => {
+                let panels = self
+                    .panels(area, settings)
+                    .into_iter()
+                    .map(|(game, area)| (game, area.as_size().into()))
+                    .collect();
+                (panels, self.rewinding > 0, None)
+            }
+        };
+        self.rewinding = self.rewinding.saturating_sub(1);
+
+        if rewind {
+            self.rewind();
+        } else {
+            self.simulate(panels);
+
+            let score = self.score();
+            if let Some(game) = self
+                .gauntlet
+                .as_mut()
+                .and_then(|g| g.update(self.ticks, score))
+            {
+                self.join(game);
+            }
+        }
+
+        self.check_state(expected_hash);
+
+        // The keyboard takes over once the replay has finished
+        if self.replay.as_ref().is_some_and(Replay::is_finished) {
+            info!("Replay finished at tick {}", self.ticks);
+            self.replay = None;
+            self.keys = Keys::new();
+        }
+    }
+
+    /// Records the hash of the state at the end of the tick, and compares it with the one
+    /// recorded in the replay
+    fn check_state(&mut self, expected_hash: Option<u64>) {
+        let hash = self.state_hash();
+
+        if expected_hash.is_some_and(|expected| expected != hash) && self.diverged.is_none() {
+            warning!(
+                "The replay has diverged from the recording at tick {}",
+                self.ticks
+            );
+            self.diverged = Some(self.ticks);
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record_hash(hash) {
+                warning!("Stopped recording the replay: {error}");
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Hash of the state of every game and the solution
+    ///
+    /// It does not change between platforms or compiler versions, so it can be used to check
+    /// that the simulation is deterministic.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.ticks.hash(&mut hasher);
+        self.active.hash(&mut hasher);
+        self.gauntlet.hash(&mut hasher);
+        self.trex.hash(&mut hasher);
+        self.solution.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Games simulated in the last tick, with the numbers that describe their state
+    ///
+    /// The games that have no state description yet have an empty one.
+    pub fn game_states(&self) -> Vec<(GameKind, Vec<f32>)> {
+        self.planned
+            .iter()
+            .map(|&(game, size)| match game {
+                GameKind::TRex => (game, self.trex.state(trex_size(size))),
+                _ => (game, Vec::new()),
+            })
+            .collect()
+    }
+
+    /// First tick where the state did not match the one of the replay
+    pub fn diverged(&self) -> Option<usize> {
+        self.diverged
+    }
+
+    /// Advances the games a tick, in the given panels
+    fn simulate(&mut self, panels: Panels) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record(&panels, self.keys) {
+                warning!("Stopped recording the replay: {error}");
+                self.recorder = None;
+            }
+        }
+
+        if self.history.len() == REWIND_TICKS {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.snapshot());
+
+        // The planner simulates the games ahead, so it depends on the size of their panels
+        if panels != self.planned {
+            self.solution.replan(self.planned_games(&panels));
+            self.planned = panels.clone();
+        }
+
+        if let Some(recorder) = &mut self.solution_recorder {
+            if let Err(error) = recorder.record(self.ticks, self.solution.keys(0)) {
+                warning!("Stopped exporting the solution: {error}");
+                self.solution_recorder = None;
+            }
+        }
+
+        for (game, size) in panels {
+            if game == GameKind::TRex {
+                self.trex.update(&mut GameContext {
+                    size: trex_size(size),
+                    keys: self.keys,
+                    solution: &self.solution,
+                    difficulty: self.config.difficulty,
+                });
+            }
+        }
+
+        self.keys.update();
+        self.solution.update();
+        self.ticks += 1;
+    }
+
+    /// Goes back to the start of the previous tick, if it is still in the history
+    fn rewind(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record_rewind() {
+                warning!("Stopped recording the replay: {error}");
+                self.recorder = None;
+            }
+        }
+
+        self.keys.update();
+
+        let Some(snapshot) = self.history.pop_back() else {
+            return;
+        };
+
+        self.active = snapshot.active;
+        self.gauntlet = snapshot.gauntlet;
+        self.trex = snapshot.trex;
+        self.solution = snapshot.solution;
+        self.ticks = snapshot.ticks;
+        self.planned = snapshot.planned;
+
+        if self.zoom.is_some_and(|game| !self.active.contains(&game)) {
+            self.zoom = None;
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            active: self.active.clone(),
+            gauntlet: self.gauntlet.clone(),
+            trex: self.trex.snapshot(),
+            solution: self.solution.clone(),
+            ticks: self.ticks,
+            planned: self.planned.clone(),
+        }
+    }
+
+    /// Current state of the games in `panels` that the solution planner can simulate
+    fn planned_games(&self, panels: &Panels) -> Vec<PlannedGame> {
+        let difficulty = self.config.difficulty;
+
+        panels
+            .iter()
+            .filter_map(|&(game, size)| match game {
+                GameKind::TRex => Some(PlannedGame::new(
+                    self.trex.world(),
+                    trex_size(size),
+                    difficulty,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Draws the games in `area`, simulated one tick every `tick_rate`
+    pub fn render(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        settings: &Settings,
+        tick_rate: Duration,
+    ) {
+        for (game, area) in self.panels(area, settings) {
+            let ghost = settings.show_ghost(game);
+            self.render_game(frame, game, area, ghost);
+        }
+
+        if let (_, Some(highway)) = Self::split_highway(area, settings) {
+            let solution = &self.solution;
+            frame.render_widget(Highway { solution }, highway);
+        }
+
+        if let Some((game, ticks)) = self.gauntlet.as_ref().and_then(Gauntlet::warning) {
+            let seconds = tick_rate.mul_f64(ticks as f64).as_secs_f64().ceil();
+            let text = format!(" {} joins in {seconds} ", game.name());
+            let banner = Rect::new(area.x, area.y, text.len() as u16, 1).intersection(area);
+            frame.render_widget(Paragraph::new(text).black().on_yellow(), banner);
+        }
+
+        if self.rewinding > 0 {
+            let text = " << Rewinding ";
+            let (games, _) = Self::split_highway(area, settings);
+            let x = games.right().saturating_sub(text.len() as u16);
+            let banner = Rect::new(x, games.y, text.len() as u16, 1).intersection(games);
+            frame.render_widget(Paragraph::new(text).black().on_cyan(), banner);
+        }
+    }
+
+    fn render_game(&mut self, frame: &mut Frame, game: GameKind, area: Rect, ghost: bool) {
+        match game {
+            GameKind::TRex => {
+                frame.render_widget(self.trex_canvas(area.as_size().into(), ghost), area)
+            }
+            _ => frame.render_widget(self.frame(game.name()), area),
+        }
+    }
+
+    fn frame(&self, title: &'static str) -> impl Widget + '_ {
+        Canvas::default()
+            .block(Block::bordered().title(title))
+            .marker(Marker::HalfBlock)
+            .paint(|ctx| {
+                ctx.draw(&Map {
+                    color: Color::Green,
+                    resolution: MapResolution::High,
+                });
+            })
+            .x_bounds([-180.0, 180.0])
+            .y_bounds([-90.0, 90.0])
+    }
+
+    fn trex_canvas(&self, canvas_size: Size<u16>, ghost: bool) -> impl Widget + '_ {
+        let size = trex_size(canvas_size);
+
+        Canvas::default()
+            .block(Block::bordered().title("T-Rex"))
+            .marker(Marker::Braille)
+            .paint(move |ctx| {
+                let canvas = &mut PixelCanvas {
+                    ctx,
+                    size,
+                    origin: TRexGame::canvas_origin(size),
+                };
+                self.trex.draw(canvas, ghost);
+            })
+            .x_bounds([0., 1.])
+            .y_bounds([0., 1.])
+    }
+}
+
+/// Pixels of the T-Rex game inside a panel of `panel_size` cells
+fn trex_size(panel_size: Size<u16>) -> Size<u16> {
+    // Without the borders, each braille character has 2x4 pixels
+    Size::new(
+        2 * panel_size.width.saturating_sub(2),
+        4 * panel_size.height.saturating_sub(2),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use std::path::Path;
+
+    fn simulate(run: &mut Run, ticks: usize) {
+        for _ in 0..ticks {
+            run.update(Rect::new(0, 0, 160, 48), &Settings::new());
+        }
+    }
+
+    fn play(replay: &str) -> Run {
+        let replay = Replay::read(replay.as_bytes(), Path::new("test.replay"), 0).unwrap();
+        let mut run = Run::from_replay(replay);
+        while run.is_replaying() {
+            run.update(Rect::default(), &Settings::new());
+        }
+        run
+    }
+
+    /// Fails when a change to the rules or the solution changes the outcome of a recorded run,
+    /// record it again with `--replay <PATH> --headless --record <PATH>` if it was intended
+    #[test]
+    fn replay_is_deterministic() {
+        let replay = include_str!("../tests/replays/determinism.replay");
+        assert_eq!(play(replay).diverged(), None);
+
+        // Change the hash of the 100th tick
+        let mut lines: Vec<_> = replay.lines().collect();
+        let hashes: Vec<_> = (0..lines.len())
+            .filter(|&i| lines[i].starts_with('='))
+            .collect();
+        lines[hashes[99]] = "= 0000000000000000";
+        assert_eq!(play(&lines.join("\n")).diverged(), Some(100));
+    }
+
+    #[test]
+    fn rewind_goes_back_in_time() {
+        let mut config = RunConfig::new();
+        config.games = vec![GameKind::TRex];
+
+        let mut straight = Run::new(config.clone());
+        simulate(&mut straight, 300);
+
+        let mut rewound = Run::new(config);
+        simulate(&mut rewound, 250);
+        rewound.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        simulate(&mut rewound, REWIND_HOLD_TICKS);
+        assert_eq!(rewound.ticks(), 250 - REWIND_HOLD_TICKS);
+        simulate(&mut rewound, 50 + REWIND_HOLD_TICKS);
+
+        assert_eq!(rewound.ticks(), straight.ticks());
+        assert_eq!(rewound.score(), straight.score());
+        for time in 0..100 {
+            assert_eq!(rewound.solution.keys(time), straight.solution.keys(time));
+        }
+    }
+
+    #[test]
+    fn rewound_ticks_are_left_out_of_the_recordings() {
+        let mut config = RunConfig::new();
+        config.games = vec![GameKind::TRex];
+        let dir = std::env::temp_dir();
+
+        // Both runs press space at the same ticks, but one of them rewinds in the middle
+        let record = |name: &str, rewind: bool| {
+            let replay = dir.join(format!("miniterms-{name}.replay"));
+            let solution = dir.join(format!("miniterms-{name}.solution"));
+            let mut run = Run::new(config.clone());
+            run.record(Recorder::create(&replay, &config).unwrap());
+            run.record_solution(SolutionRecorder::create(&solution, &config).unwrap());
+
+            let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+            for tick in [30, 60, 110, 200] {
+                if rewind && tick == 110 {
+                    let ticks = 100 - run.ticks();
+                    simulate(&mut run, ticks);
+                    run.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+                    simulate(&mut run, REWIND_HOLD_TICKS);
+                }
+                let ticks = tick - run.ticks();
+                simulate(&mut run, ticks);
+                run.handle_key_event(space);
+            }
+            drop(run);
+
+            let keys: Vec<_> = Replay::load(&replay).unwrap().into_keys().collect();
+            let exported = std::fs::read_to_string(&solution).unwrap();
+            let _ = std::fs::remove_file(replay);
+            let _ = std::fs::remove_file(solution);
+            (keys, exported)
+        };
+
+        let (straight_keys, straight_solution) = record("straight", false);
+        let (rewound_keys, rewound_solution) = record("rewound", true);
+        assert_eq!(rewound_keys.len(), 200);
+        assert_eq!(rewound_keys, straight_keys);
+        assert_eq!(rewound_solution, straight_solution);
+    }
+}
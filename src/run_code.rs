@@ -0,0 +1,219 @@
+use crate::games::GameKind;
+use crate::run_config::{Difficulty, RunConfig, RunMode, SolutionSource, Unlock};
+use crate::seed::Seed;
+
+/// Version of the game rules, runs with different rules can not be reproduced from a code
+pub const RULES_VERSION: u8 = 6;
+
+/// Crockford's base32, without the letters that are easily confused
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const CODE_DIGITS: usize = 20;
+const SEED_DIGITS: usize = 13;
+
+// Bit layout of a run code, from the least significant bit. The seed uses the first 64 bits.
+const GAMES_SHIFT: u32 = 64;
+const MODE_SHIFT: u32 = 72;
+const DIFFICULTY_SHIFT: u32 = 74;
+const SOLUTION_SHIFT: u32 = 77;
+const VERSION_SHIFT: u32 = 80;
+const CHECK_SHIFT: u32 = 88;
+const CHECK_BITS: u32 = 12;
+
+/// Short code that reproduces the whole run, like `1A2B-3C4D-5E6F-7G8H-9J0K`
+///
+/// Runs that use a recorded solution depend on a local file and have no code, and neither do
+/// the runs with other unlock thresholds than the default ones.
+pub fn encode(config: &RunConfig) -> Option<String> {
+    if config.unlock != Unlock::DEFAULT {
+        return None;
+    }
+
+    let solution = SolutionSource::GENERATED
+        .iter()
+        .position(|source| *source == config.solution)?;
+
+    let games = GameKind::ALL
+        .iter()
+        .enumerate()
+        .filter(|(_, game)| config.has_game(**game))
+        .fold(0, |games, (i, _)| games | 1 << i);
+
+    let mode = match config.mode {
+        RunMode::Classic => 0,
+        RunMode::Gauntlet => 1,
+    };
+
+    let difficulty = Difficulty::ALL
+        .iter()
+        .position(|&d| d == config.difficulty)
+        .unwrap_or(0);
+
+    let value = Seed::from_text(&config.seed).value() as u128
+        | (games as u128) << GAMES_SHIFT
+        | (mode as u128) << MODE_SHIFT
+        | (difficulty as u128) << DIFFICULTY_SHIFT
+        | (solution as u128) << SOLUTION_SHIFT
+        | (RULES_VERSION as u128) << VERSION_SHIFT;
+    let value = value | (checksum(value) as u128) << CHECK_SHIFT;
+
+    let digits = encode_digits(value, CODE_DIGITS);
+    let groups: Vec<_> = digits
+        .as_bytes()
+        .chunks(4)
+        .map(String::from_utf8_lossy)
+        .collect();
+    Some(groups.join("-"))
+}
+
+/// Reads a code written by [`encode`], dashes and spaces are ignored
+pub fn decode(code: &str) -> Result<RunConfig, String> {
+    let Some((value, CODE_DIGITS)) = decode_digits(code) else {
+        return Err(format!("invalid run code '{code}'"));
+    };
+
+    let field = |shift: u32, bits: u32| (value >> shift) as usize & ((1 << bits) - 1);
+
+    let without_check = value & ((1 << CHECK_SHIFT) - 1);
+    if field(CHECK_SHIFT, CHECK_BITS) != checksum(without_check) as usize {
+        return Err(format!("invalid run code '{code}', check for typos"));
+    }
+
+    let version = field(VERSION_SHIFT, 8);
+    if version != RULES_VERSION as usize {
+        return Err(format!(
+            "the run code is for version {version} of the rules, this is version {RULES_VERSION}"
+        ));
+    }
+
+    let games: Vec<_> = GameKind::ALL
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| field(GAMES_SHIFT, 8) & 1 << i != 0)
+        .map(|(_, &game)| game)
+        .collect();
+    if games.is_empty() {
+        return Err(format!("the run code '{code}' has no games"));
+    }
+
+    let mode = match field(MODE_SHIFT, 2) {
+        0 => RunMode::Classic,
+        _ => RunMode::Gauntlet,
+    };
+
+    let Some(&difficulty) = Difficulty::ALL.get(field(DIFFICULTY_SHIFT, 3)) else {
+        return Err(format!("invalid difficulty in the run code '{code}'"));
+    };
+
+    let Some(solution) = SolutionSource::GENERATED.get(field(SOLUTION_SHIFT, 3)) else {
+        return Err(format!("invalid solution in the run code '{code}'"));
+    };
+
+    Ok(RunConfig {
+        games,
+        mode,
+        difficulty,
+        seed: Seed::from_value(value as u64).to_string(),
+        solution: solution.clone(),
+        unlock: Unlock::DEFAULT,
+    })
+}
+
+pub fn encode_u64(value: u64) -> String {
+    encode_digits(value as u128, SEED_DIGITS)
+}
+
+pub fn decode_u64(text: &str) -> Option<u64> {
+    match decode_digits(text)? {
+        (value, SEED_DIGITS) => value.try_into().ok(),
+        _ => None,
+    }
+}
+
+/// Weighted sum of the digits, it detects a wrong digit and most swapped digits
+fn checksum(value: u128) -> u16 {
+    let digits = (0..CODE_DIGITS).map(|i| (value >> (5 * i)) as u32 & 31);
+    // 4093 is the largest prime that fits in `CHECK_BITS`
+    digits.fold(0, |sum, digit| (sum * 31 + digit + 1) % 4093) as u16
+}
+
+fn encode_digits(value: u128, digits: usize) -> String {
+    (0..digits)
+        .rev()
+        .map(|i| ALPHABET[(value >> (5 * i)) as usize & 31] as char)
+        .collect()
+}
+
+/// Returns the value and the amount of digits read
+fn decode_digits(text: &str) -> Option<(u128, usize)> {
+    let mut value: u128 = 0;
+    let mut digits = 0;
+
+    for c in text.chars().filter(|&c| c != '-' && !c.is_whitespace()) {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let digit = ALPHABET.iter().position(|&a| a as char == c)?;
+
+        value = value.checked_mul(32)? | digit as u128;
+        digits += 1;
+    }
+
+    Some((value, digits))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn code_round_trip() {
+        let config = RunConfig {
+            games: vec![GameKind::TRex, GameKind::Breakout, GameKind::Packman],
+            mode: RunMode::Gauntlet,
+            difficulty: Difficulty::Hard,
+            seed: "hello".to_string(),
+            solution: SolutionSource::Rhythmic,
+            unlock: Unlock::DEFAULT,
+        };
+
+        let code = encode(&config).unwrap();
+        assert_eq!(code.len(), CODE_DIGITS + 4);
+
+        let decoded = decode(&code.to_lowercase()).unwrap();
+        assert_eq!(decoded.games, config.games);
+        assert_eq!(decoded.mode, config.mode);
+        assert_eq!(decoded.difficulty, config.difficulty);
+        assert_eq!(decoded.solution, config.solution);
+        assert_eq!(Seed::from_text(&decoded.seed), Seed::from_text("hello"));
+        assert_eq!(encode(&decoded), Some(code.clone()));
+
+        // Typing the next letter of the alphabet is detected
+        let mut typo = code.clone().into_bytes();
+        let digit = ALPHABET.iter().position(|&a| a == typo[10]).unwrap();
+        typo[10] = ALPHABET[(digit + 1) % 32];
+        assert!(decode(&String::from_utf8(typo).unwrap()).is_err());
+        assert!(decode("1234").is_err());
+
+        let unlock = Unlock {
+            ticks: 10,
+            score: 1,
+        };
+        assert_eq!(
+            encode(&RunConfig {
+                unlock,
+                ..config.clone()
+            }),
+            None
+        );
+
+        let recording = SolutionSource::Recording("run.replay".into());
+        let config = RunConfig {
+            solution: recording,
+            ..config
+        };
+        assert_eq!(encode(&config), None);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::games::GameKind;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RunMode {
+    /// All the games are played from the start
+    Classic,
+    /// Starts with a single game, the others join during the run
+    Gauntlet,
+}
+
+/// Strategy that generates the solution of the run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolutionSource {
+    Phased,
+    Markov,
+    Rhythmic,
+    /// Keys of a replay file
+    Recording(PathBuf),
+}
+
+/// When the games join a gauntlet run, the n-th one after n times `ticks` ticks or n times
+/// `score` points since the start of the run, whichever comes first
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Unlock {
+    pub ticks: usize,
+    pub score: usize,
+}
+
+/// Everything that is chosen before starting a run
+#[derive(Clone, Debug)]
+pub struct RunConfig {
+    pub games: Vec<GameKind>,
+    pub mode: RunMode,
+    pub difficulty: Difficulty,
+    pub seed: String,
+    pub solution: SolutionSource,
+    pub unlock: Unlock,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Multiplier for the pace of the games
+    pub fn speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    pub fn next(self) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+
+    pub fn previous(self) -> Difficulty {
+        let len = Difficulty::ALL.len();
+        let index = Difficulty::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Difficulty::ALL[(index + len - 1) % len]
+    }
+}
+
+impl RunMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            RunMode::Classic => "Classic",
+            RunMode::Gauntlet => "Gauntlet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RunMode> {
+        [RunMode::Classic, RunMode::Gauntlet]
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn toggle(self) -> RunMode {
+        match self {
+            RunMode::Classic => RunMode::Gauntlet,
+            RunMode::Gauntlet => RunMode::Classic,
+        }
+    }
+}
+
+impl Unlock {
+    pub const DEFAULT: Unlock = Unlock {
+        ticks: 45 * 25,
+        score: 15,
+    };
+}
+
+impl SolutionSource {
+    /// Strategies that only depend on the seed
+    pub const GENERATED: [SolutionSource; 3] = [
+        SolutionSource::Phased,
+        SolutionSource::Markov,
+        SolutionSource::Rhythmic,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            SolutionSource::Phased => "Phased".to_string(),
+            SolutionSource::Markov => "Markov".to_string(),
+            SolutionSource::Rhythmic => "Rhythmic".to_string(),
+            SolutionSource::Recording(path) => format!("Recording:{}", path.display()),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SolutionSource> {
+        if let Some(path) = name.strip_prefix("Recording:") {
+            return Some(SolutionSource::Recording(path.into()));
+        }
+
+        SolutionSource::GENERATED
+            .into_iter()
+            .find(|source| source.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Cycles through the generated strategies
+    pub fn next(&self) -> SolutionSource {
+        let index = SolutionSource::GENERATED.iter().position(|s| s == self);
+        let next = index.map_or(0, |i| (i + 1) % SolutionSource::GENERATED.len());
+        SolutionSource::GENERATED[next].clone()
+    }
+}
+
+impl RunConfig {
+    pub fn new() -> Self {
+        Self {
+            games: GameKind::ALL.to_vec(),
+            mode: RunMode::Classic,
+            difficulty: Difficulty::Normal,
+            seed: String::new(),
+            solution: SolutionSource::Phased,
+            unlock: Unlock::DEFAULT,
+        }
+    }
+
+    pub fn has_game(&self, game: GameKind) -> bool {
+        self.games.contains(&game)
+    }
+
+    /// Adds or removes `game`, keeping the games in the order of [`GameKind::ALL`]
+    pub fn toggle_game(&mut self, game: GameKind) {
+        if self.has_game(game) {
+            self.games.retain(|&g| g != game);
+        } else {
+            self.games.push(game);
+            self.games
+                .sort_by_key(|g| GameKind::ALL.iter().position(|a| a == g));
+        }
+    }
+}
+
+impl RunConfig {
+    /// `key value` pairs describing the config, they can be read back with [`RunConfig::set_field`]
+    pub fn fields(&self) -> [(&'static str, String); 6] {
+        let games: Vec<_> = self.games.iter().map(|game| game.id()).collect();
+        [
+            ("games", games.join(",")),
+            ("mode", self.mode.name().to_string()),
+            ("difficulty", self.difficulty.name().to_string()),
+            ("seed", self.seed.clone()),
+            ("solution", self.solution.name()),
+            (
+                "unlock",
+                format!("{},{}", self.unlock.ticks, self.unlock.score),
+            ),
+        ]
+    }
+
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "games" => self.games = parse_games(value)?,
+            "mode" => {
+                self.mode = RunMode::from_name(value).ok_or(format!("invalid mode '{value}'"))?
+            }
+            "difficulty" => {
+                self.difficulty =
+                    Difficulty::from_name(value).ok_or(format!("invalid difficulty '{value}'"))?
+            }
+            "seed" => self.seed = value.to_string(),
+            "solution" => {
+                self.solution =
+                    SolutionSource::from_name(value).ok_or(format!("invalid solution '{value}'"))?
+            }
+            "unlock" => {
+                let unlock = value.split_once(',').and_then(|(ticks, score)| {
+                    Some(Unlock {
+                        ticks: ticks.trim().parse().ok()?,
+                        score: score.trim().parse().ok()?,
+                    })
+                });
+                self.unlock = unlock.ok_or(format!("invalid unlock '{value}'"))?;
+            }
+            _ => return Err(format!("unknown config field '{key}'")),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma separated list of [`GameKind::id`]
+pub fn parse_games(text: &str) -> Result<Vec<GameKind>, String> {
+    let mut games = Vec::new();
+
+    for id in text.split(',').map(str::trim) {
+        let Some(game) = GameKind::from_id(id) else {
+            let ids: Vec<_> = GameKind::ALL.iter().map(|game| game.id()).collect();
+            return Err(format!(
+                "unknown game '{id}', expected one of: {}",
+                ids.join(", ")
+            ));
+        };
+
+        if !games.contains(&game) {
+            games.push(game);
+        }
+    }
+
+    Ok(games)
+}
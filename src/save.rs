@@ -0,0 +1,285 @@
+use crate::run::Run;
+use crate::run_code::RULES_VERSION;
+use crate::run_config::RunConfig;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SAVE_HEADER: &str = "# miniterms save v2";
+
+/// Writes `run` to a save file
+///
+/// File format: the header, the rules version and the config of the run as `# key value`
+/// lines, then the state of the run as written by [`Run::save_state`], and a last line with
+/// the hash of the state to check that it is read back as it was.
+pub fn save(run: &Run, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = format!("{SAVE_HEADER}\n# rules {RULES_VERSION}\n");
+    for (key, value) in run.config().fields() {
+        let _ = writeln!(file, "# {key} {value}");
+    }
+
+    let mut state = StateWriter::new();
+    run.save_state(&mut state);
+    state
+        .line("hash")
+        .value(format!("{:016x}", run.state_hash()));
+    file.push_str(&state.text);
+
+    fs::write(path, file)
+}
+
+/// Resumes the run of a save file, in the same state it was saved
+///
+/// Fails if the save is for other rules, or if the state is not read back as it was saved.
+pub fn load(path: &Path) -> io::Result<Run> {
+    let invalid = |line: usize, message: String| {
+        let message = format!("{}:{}: {message}", path.display(), line + 1);
+        io::Error::new(io::ErrorKind::InvalidData, message)
+    };
+
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines().enumerate().peekable();
+
+    if lines.next().map(|(_, line)| line) != Some(SAVE_HEADER) {
+        return Err(invalid(0, "not a miniterms save".to_string()));
+    }
+
+    let rules = lines
+        .next()
+        .and_then(|(_, line)| line.strip_prefix("# rules "));
+    match rules.and_then(|rules| rules.trim().parse::<u8>().ok()) {
+        Some(RULES_VERSION) => {}
+        Some(rules) => {
+            return Err(invalid(
+                1,
+                format!(
+                    "the save is for version {rules} of the rules, this is version {RULES_VERSION}"
+                ),
+            ))
+        }
+        None => return Err(invalid(1, "missing 'rules' in the header".to_string())),
+    }
+
+    let mut config = RunConfig::new();
+    while let Some((number, field)) = lines.next_if(|(_, line)| line.starts_with("# ")) {
+        let (key, value) = field[2..].split_once(' ').unwrap_or((&field[2..], ""));
+        config
+            .set_field(key, value)
+            .map_err(|error| invalid(number, error))?;
+    }
+
+    let mut state = StateReader::new(lines);
+    let state_error = |state: &StateReader, error: String| invalid(state.number, error);
+
+    let run = Run::load_state(config, &mut state).map_err(|error| state_error(&state, error))?;
+
+    let hash = state
+        .line("hash")
+        .and_then(|()| state.value::<String>())
+        .map_err(|error| state_error(&state, error))?;
+    if u64::from_str_radix(&hash, 16) != Ok(run.state_hash()) {
+        return Err(state_error(
+            &state,
+            "the run is not in the state it was saved".to_string(),
+        ));
+    }
+
+    Ok(run)
+}
+
+/// State of a run as lines of `name value value...`
+pub struct StateWriter {
+    text: String,
+}
+
+impl StateWriter {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+        }
+    }
+
+    /// Starts the line `name`, the values written next are added to it
+    pub fn line(&mut self, name: &str) -> &mut Self {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+        self.text.push_str(name);
+        self
+    }
+
+    /// Adds a value to the line, it must not contain whitespace
+    pub fn value(&mut self, value: impl Display) -> &mut Self {
+        let _ = write!(self.text, " {value}");
+        self
+    }
+
+    /// Adds the seed, stream and position of `rng`
+    pub fn rng(&mut self, rng: &ChaCha8Rng) -> &mut Self {
+        let seed: String = rng.get_seed().iter().map(|b| format!("{b:02x}")).collect();
+        self.value(seed)
+            .value(rng.get_stream())
+            .value(rng.get_word_pos())
+    }
+}
+
+/// Reads the lines of a [`StateWriter`], in the same order they were written
+pub struct StateReader<'a> {
+    lines: Box<dyn Iterator<Item = (usize, &'a str)> + 'a>,
+    /// Number of the current line in the file
+    number: usize,
+    /// Values of the current line that have not been read
+    rest: &'a str,
+}
+
+impl<'a> StateReader<'a> {
+    fn new(lines: impl Iterator<Item = (usize, &'a str)> + 'a) -> Self {
+        Self {
+            lines: Box::new(lines),
+            number: 0,
+            rest: "",
+        }
+    }
+
+    /// Goes to the next line, which must be `name`
+    pub fn line(&mut self, name: &str) -> Result<(), String> {
+        let Some((number, line)) = self.lines.next() else {
+            return Err(format!("missing '{name}'"));
+        };
+        self.number = number;
+
+        let (found, rest) = line.split_once(' ').unwrap_or((line, ""));
+        if found != name {
+            return Err(format!("expected '{name}', found '{found}'"));
+        }
+
+        self.rest = rest;
+        Ok(())
+    }
+
+    /// Reads the next value of the line
+    pub fn value<T: FromStr>(&mut self) -> Result<T, String> {
+        let rest = self.rest.trim_start();
+        let (value, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        self.rest = rest;
+
+        if value.is_empty() {
+            return Err("missing value".to_string());
+        }
+        value
+            .parse()
+            .map_err(|_| format!("invalid value '{value}'"))
+    }
+
+    /// Reads the values of the line that are left
+    pub fn rest(&mut self) -> &'a str {
+        std::mem::take(&mut self.rest).trim()
+    }
+
+    /// Reads a generator written by [`StateWriter::rng`]
+    pub fn rng(&mut self) -> Result<ChaCha8Rng, String> {
+        let text: String = self.value()?;
+        let mut seed = [0; 32];
+        let valid = text.len() == 2 * seed.len()
+            && seed.iter_mut().enumerate().all(|(i, byte)| {
+                let hex = text.get(2 * i..2 * i + 2);
+                hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .map(|value| *byte = value)
+                    .is_some()
+            });
+        if !valid {
+            return Err(format!("invalid seed '{text}'"));
+        }
+
+        let mut rng = ChaCha8Rng::from_seed(seed);
+        rng.set_stream(self.value()?);
+        rng.set_word_pos(self.value()?);
+        Ok(rng)
+    }
+}
+
+/// Save file used when none is given
+pub fn default_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("save"))
+}
+
+/// Directory where miniterms keeps its files
+pub fn data_dir() -> Option<PathBuf> {
+    let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+
+    Some(data_dir.join("miniterms"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::GameKind;
+    use crate::settings::Settings;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn save_and_resume() {
+        let path = std::env::temp_dir().join("miniterms-save-and-resume");
+        let settings = Settings::new();
+        let area = Rect::new(0, 0, 160, 48);
+
+        let mut config = RunConfig::new();
+        config.games = vec![GameKind::TRex];
+        let mut run = Run::new(config);
+        for tick in 0..400 {
+            if tick % 50 == 0 {
+                run.handle_key_event(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+            }
+            if tick == 300 {
+                run.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+            }
+            run.update(area, &settings);
+        }
+        save(&run, &path).unwrap();
+
+        let mut resumed = load(&path).unwrap();
+        assert_eq!(resumed.ticks(), run.ticks());
+        assert_eq!(resumed.state_hash(), run.state_hash());
+
+        for _ in 0..2000 {
+            run.update(area, &settings);
+            resumed.update(area, &settings);
+        }
+        assert_eq!(resumed.state_hash(), run.state_hash());
+
+        let content = fs::read_to_string(&path).unwrap();
+        let rules = format!("# rules {RULES_VERSION}\n");
+        fs::write(&path, content.replacen(&rules, "# rules 0\n", 1)).unwrap();
+        let error = load(&path).err().unwrap();
+        assert!(error.to_string().contains("version 0"), "{error}");
+
+        // The strategy is not part of the hash, but it is checked when it is read
+        let strategy = content
+            .lines()
+            .find(|line| line.starts_with("strategy "))
+            .unwrap();
+        let mut values: Vec<_> = strategy.split(' ').collect();
+        values[3] = "1000";
+        fs::write(&path, content.replacen(strategy, &values.join(" "), 1)).unwrap();
+        let error = load(&path).err().unwrap();
+        assert!(error.to_string().contains("invalid phase time"), "{error}");
+
+        let tampered = content.replacen("\nticks ", "\nticks 1", 1);
+        fs::write(&path, tampered).unwrap();
+        let error = load(&path).err().unwrap();
+        let _ = fs::remove_file(&path);
+        assert!(error.to_string().contains("not in the state"), "{error}");
+    }
+}
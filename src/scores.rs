@@ -0,0 +1,125 @@
+use crate::games::GameKind;
+use crate::log::warning;
+use crate::run_config::{Difficulty, RunConfig};
+use crate::save;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Amount of scores kept in the scores file
+const MAX_SCORES: usize = 10;
+
+/// Files without it kept the ticks of each run instead of its points, and are ignored
+const SCORES_HEADER: &str = "# miniterms scores v2";
+
+#[derive(Clone, Debug)]
+pub struct Score {
+    /// Points of the run when it ended
+    pub points: usize,
+    pub difficulty: Difficulty,
+    pub games: Vec<GameKind>,
+    pub seed: String,
+}
+
+/// Best scores, sorted from best to worst
+pub struct HighScores {
+    scores: Vec<Score>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let mut scores = Vec::new();
+
+        if let Some(content) = scores_path().and_then(|path| fs::read_to_string(path).ok()) {
+            let mut lines = content.lines();
+            if lines.next() == Some(SCORES_HEADER) {
+                scores.extend(lines.filter_map(Score::parse));
+            }
+        }
+
+        scores.sort_by_key(|score| std::cmp::Reverse(score.points));
+        Self { scores }
+    }
+
+    pub fn scores(&self) -> &[Score] {
+        &self.scores
+    }
+
+    /// Records the score and saves the scores file
+    pub fn add(&mut self, config: &RunConfig, points: usize) {
+        if points == 0 {
+            return;
+        }
+
+        let index = self.scores.partition_point(|score| score.points >= points);
+        if index >= MAX_SCORES {
+            return;
+        }
+
+        self.scores.insert(
+            index,
+            Score {
+                points,
+                difficulty: config.difficulty,
+                games: config.games.clone(),
+                seed: config.seed.clone(),
+            },
+        );
+        self.scores.truncate(MAX_SCORES);
+
+        if let Err(error) = self.save() {
+            warning!("Could not save the high scores: {error}");
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = scores_path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = fs::File::create(path)?;
+        writeln!(file, "{SCORES_HEADER}")?;
+        for score in &self.scores {
+            writeln!(file, "{}", score.line())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Score {
+    /// Format: `points difficulty game,game,... seed`
+    fn line(&self) -> String {
+        let games: Vec<_> = self.games.iter().map(|game| game.id()).collect();
+        format!(
+            "{} {} {} {}",
+            self.points,
+            self.difficulty.name(),
+            games.join(","),
+            self.seed
+        )
+    }
+
+    fn parse(line: &str) -> Option<Score> {
+        let mut parts = line.splitn(4, ' ');
+
+        Some(Score {
+            points: parts.next()?.parse().ok()?,
+            difficulty: Difficulty::from_name(parts.next()?)?,
+            games: parts
+                .next()?
+                .split(',')
+                .map(GameKind::from_id)
+                .collect::<Option<_>>()?,
+            seed: parts.next().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+fn scores_path() -> Option<PathBuf> {
+    Some(save::data_dir()?.join("scores"))
+}
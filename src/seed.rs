@@ -0,0 +1,143 @@
+use crate::run_code;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt::{self, Display};
+use std::hash::Hasher;
+
+/// Master seed of a run, every source of randomness derives its own stream from it
+///
+/// Streams are identified by name, so adding a new stream does not change the others.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Seed(u64);
+
+impl Seed {
+    /// Hashes the text, except for the `@` form written by [`Display`] which is read back as is
+    pub fn from_text(text: &str) -> Seed {
+        let raw = text.strip_prefix('@').and_then(run_code::decode_u64);
+        Seed(raw.unwrap_or_else(|| fnv1a(text.as_bytes())))
+    }
+
+    pub fn from_value(value: u64) -> Seed {
+        Seed(value)
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Random generator of `stream`, it only depends on the master seed and the stream name
+    ///
+    /// ChaCha gives the same numbers on every platform and version of `rand`, unlike `SmallRng`.
+    pub fn rng(&self, stream: &str) -> ChaCha8Rng {
+        ChaCha8Rng::from_seed(self.stream_seed(stream))
+    }
+
+    fn stream_seed(&self, stream: &str) -> [u8; 32] {
+        let mut state = self.0 ^ splitmix64(&mut fnv1a(stream.as_bytes()));
+
+        let mut seed = [0; 32];
+        for chunk in seed.chunks_exact_mut(8) {
+            chunk.copy_from_slice(&splitmix64(&mut state).to_le_bytes());
+        }
+        seed
+    }
+}
+
+impl Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", run_code::encode_u64(self.0))
+    }
+}
+
+/// Hash that does not change between platforms or compiler versions, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// [`Hasher`] of [`fnv1a`], integers are hashed in little endian and sizes as 64 bits
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i16(&mut self, n: i16) {
+        self.write_u16(n as u16);
+    }
+
+    fn write_i32(&mut self, n: i32) {
+        self.write_u32(n as u32);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.write_u64(n as i64 as u64);
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streams_are_stable_and_independent() {
+        let seed = Seed::from_text("miniterms");
+        assert_eq!(seed, Seed::from_text("miniterms"));
+        assert_ne!(seed, Seed::from_text("miniterm"));
+
+        assert_eq!(seed.stream_seed("trex"), seed.stream_seed("trex"));
+        assert_ne!(seed.stream_seed("trex"), seed.stream_seed("tetris"));
+        assert_ne!(
+            seed.stream_seed("trex"),
+            Seed::from_text("other").stream_seed("trex")
+        );
+
+        let raw = Seed::from_text(&seed.to_string());
+        assert_eq!(raw, seed);
+
+        // Changing the derivation would break every recorded replay and shared seed
+        assert_eq!(fnv1a(b"miniterms"), 0xe7b5_b83e_32ac_91b9);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::games::GameKind;
+
+pub struct Settings {
+    pub pause_on_focus_lost: bool,
+    /// Keep running the other games in a strip while a game is zoomed, instead of pausing them
+    pub zoom_strip: bool,
+    /// Show the upcoming keys of the solution
+    pub show_highway: bool,
+    /// Draw the player that follows the solution, dimmed under the real one
+    pub show_ghost: bool,
+    /// Games that ignore `show_ghost`, in the order of [`GameKind::ALL`]
+    ghost_overrides: [Option<bool>; GameKind::ALL.len()],
+}
+
+pub struct SettingItem {
+    pub name: String,
+    pub value: String,
+}
+
+/// Entry of the settings menu
+#[derive(Copy, Clone)]
+enum Setting {
+    PauseOnFocusLost,
+    ZoomStrip,
+    ShowHighway,
+    ShowGhost,
+    /// Each game can override the ghost setting
+    GhostOverride(GameKind),
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            pause_on_focus_lost: true,
+            zoom_strip: true,
+            show_highway: true,
+            show_ghost: true,
+            ghost_overrides: [None; GameKind::ALL.len()],
+        }
+    }
+
+    pub fn show_ghost(&self, game: GameKind) -> bool {
+        self.ghost_override(game).unwrap_or(self.show_ghost)
+    }
+
+    fn ghost_override(&self, game: GameKind) -> Option<bool> {
+        let index = GameKind::ALL.iter().position(|&g| g == game)?;
+        self.ghost_overrides[index]
+    }
+
+    pub fn items(&self) -> Vec<SettingItem> {
+        Setting::all()
+            .map(|setting| SettingItem {
+                name: setting.name(),
+                value: self.value(setting),
+            })
+            .collect()
+    }
+
+    fn value(&self, setting: Setting) -> String {
+        match setting {
+            Setting::PauseOnFocusLost => on_off(self.pause_on_focus_lost),
+            Setting::ZoomStrip => if self.zoom_strip { "Run" } else { "Pause" }.to_string(),
+            Setting::ShowHighway => on_off(self.show_highway),
+            Setting::ShowGhost => on_off(self.show_ghost),
+            Setting::GhostOverride(game) => match self.ghost_override(game) {
+                Some(show) => on_off(show),
+                None => "Auto".to_string(),
+            },
+        }
+    }
+
+    /// Changes the value of the item at `index` of [`Settings::items`]
+    pub fn toggle(&mut self, index: usize) {
+        let Some(setting) = Setting::all().nth(index) else {
+            return;
+        };
+
+        match setting {
+            Setting::PauseOnFocusLost => self.pause_on_focus_lost = !self.pause_on_focus_lost,
+            Setting::ZoomStrip => self.zoom_strip = !self.zoom_strip,
+            Setting::ShowHighway => self.show_highway = !self.show_highway,
+            Setting::ShowGhost => self.show_ghost = !self.show_ghost,
+            Setting::GhostOverride(game) => {
+                let index = GameKind::ALL.iter().position(|&g| g == game);
+                if let Some(ghost) = index.map(|index| &mut self.ghost_overrides[index]) {
+                    // Auto, On, Off, and back to Auto
+                    *ghost = match *ghost {
+                        None => Some(true),
+                        Some(true) => Some(false),
+                        Some(false) => None,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl Setting {
+    /// Every setting, in the order they are shown in the menu
+    fn all() -> impl Iterator<Item = Setting> {
+        [
+            Setting::PauseOnFocusLost,
+            Setting::ZoomStrip,
+            Setting::ShowHighway,
+            Setting::ShowGhost,
+        ]
+        .into_iter()
+        .chain(GameKind::ALL.into_iter().map(Setting::GhostOverride))
+    }
+
+    fn name(self) -> String {
+        match self {
+            Setting::PauseOnFocusLost => "Pause on focus lost".to_string(),
+            Setting::ZoomStrip => "Zoom: others".to_string(),
+            Setting::ShowHighway => "Next keys (F3)".to_string(),
+            Setting::ShowGhost => "Solution ghost (F4)".to_string(),
+            Setting::GhostOverride(game) => format!("  {}", game.name()),
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_string()
+}
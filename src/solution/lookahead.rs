@@ -0,0 +1,103 @@
+use super::{Solution, SOLUTION_SIZE};
+use crate::input::Keys;
+
+/// Future states of a player that follows the [`Solution`], one for each of its ticks
+///
+/// Kept in lockstep with the ring buffer of the solution: each tick only the state after its
+/// newest keys is simulated. When the solution is planned again every state is simulated anew.
+#[derive(Clone)]
+pub struct Lookahead<T> {
+    first_index: usize,
+    states: Vec<T>,
+    /// Plan and tick of the solution the states were simulated for
+    synced: Option<(usize, usize)>,
+}
+
+impl<T: Copy + PartialEq> Lookahead<T> {
+    pub fn new() -> Self {
+        Self {
+            first_index: 0,
+            states: Vec::with_capacity(SOLUTION_SIZE),
+            synced: None,
+        }
+    }
+
+    /// Catches up with `solution`
+    ///
+    /// `current` is the state after following `solution.keys(0)`, and `step` advances a state
+    /// a tick with the given keys.
+    pub fn sync(&mut self, current: T, solution: &Solution, step: impl Fn(&mut T, Keys)) {
+        let synced = (solution.plan(), solution.ticks());
+
+        match self.synced {
+            Some(previous) if previous == synced => {}
+            Some((plan, ticks)) if plan == synced.0 && ticks + 1 == synced.1 => {
+                let mut state = self.get(SOLUTION_SIZE - 1);
+                step(&mut state, solution.keys(SOLUTION_SIZE - 1));
+
+                self.states[self.first_index] = state;
+                self.first_index = (self.first_index + 1) % SOLUTION_SIZE;
+                debug_assert!(self.get(0) == current);
+            }
+            _ => {
+                let mut state = current;
+                self.states.clear();
+                self.states.push(state);
+                for time in 1..SOLUTION_SIZE {
+                    step(&mut state, solution.keys(time));
+                    self.states.push(state);
+                }
+                self.first_index = 0;
+            }
+        }
+
+        self.synced = Some(synced);
+    }
+
+    /// State after following the solution until `time` ticks from now
+    pub fn get(&self, time: usize) -> T {
+        if time >= SOLUTION_SIZE {
+            panic!("Index out of bounds");
+        }
+
+        self.states[(self.first_index + time) % SOLUTION_SIZE]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::run_config::{Difficulty, SolutionSource};
+    use crate::seed::Seed;
+    use crate::solution;
+
+    #[test]
+    fn lookahead_follows_the_solution() {
+        let seed = Seed::from_text("lookahead");
+        let strategy = solution::strategy(&SolutionSource::Markov, &seed, Difficulty::Normal);
+        let mut solution = Solution::new(strategy);
+
+        // Counts the ticks with some key pressed
+        let step = |presses: &mut usize, keys: Keys| *presses += keys.any_pressed() as usize;
+        let mut presses = 0;
+        let mut lookahead = Lookahead::new();
+
+        for tick in 0..3000 {
+            if tick == 1000 {
+                solution.replan(Vec::new());
+            }
+
+            step(&mut presses, solution.keys(0));
+            lookahead.sync(presses, &solution, step);
+
+            if tick % 300 == 0 {
+                let mut expected = presses;
+                for time in 1..SOLUTION_SIZE {
+                    step(&mut expected, solution.keys(time));
+                    assert_eq!(lookahead.get(time), expected);
+                }
+            }
+            solution.update();
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use super::{SolutionStrategy, SolutionTuning};
+use crate::input::{Key, Keys};
+use crate::save::{StateReader, StateWriter};
+use rand_chacha::ChaCha8Rng;
+
+/// Chance per tick of pressing a released key of average weight
+const PRESS_CHANCE: f64 = 0.02;
+/// Chance per tick of releasing a pressed key
+const RELEASE_CHANCE: f64 = 0.15;
+
+/// Each key is a two state Markov chain, released or pressed
+///
+/// A key stays pressed for `1 / RELEASE_CHANCE` ticks on average.
+#[derive(Clone)]
+pub struct MarkovStrategy {
+    keys: Keys,
+    tuning: SolutionTuning,
+    rng: ChaCha8Rng,
+}
+
+impl MarkovStrategy {
+    pub fn new(tuning: SolutionTuning, rng: ChaCha8Rng) -> Self {
+        Self {
+            keys: Keys::new(),
+            tuning,
+            rng,
+        }
+    }
+}
+
+impl SolutionStrategy for MarkovStrategy {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy> {
+        Box::new(self.clone())
+    }
+
+    fn next(&mut self) -> Keys {
+        self.keys.update();
+
+        for key in Key::ALL {
+            if self.keys.pressing(key) {
+                if self.tuning.change(&mut self.rng, RELEASE_CHANCE) {
+                    self.keys.release(key);
+                }
+            } else {
+                let chance = PRESS_CHANCE * self.tuning.key_weight(key);
+                if self.tuning.change(&mut self.rng, chance) {
+                    self.keys.press(key);
+                }
+            }
+        }
+
+        self.keys
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.line("strategy").value(self.keys).rng(&self.rng);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("strategy")?;
+        self.keys = state.value()?;
+        self.rng = state.rng()?;
+        Ok(())
+    }
+}
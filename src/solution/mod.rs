@@ -0,0 +1,377 @@
+use crate::input::Keys;
+use crate::log::warning;
+use crate::run_config::{Difficulty, SolutionSource};
+use crate::save::{StateReader, StateWriter};
+use crate::seed::Seed;
+use std::hash::{Hash, Hasher};
+
+mod lookahead;
+mod markov;
+mod phased;
+mod planner;
+mod recording;
+mod rhythmic;
+mod tuning;
+
+pub use lookahead::Lookahead;
+pub use markov::MarkovStrategy;
+pub use phased::PhasedStrategy;
+pub use planner::{PlannedGame, Planner};
+pub use recording::{RecordingStrategy, SolutionRecorder};
+pub use rhythmic::RhythmicStrategy;
+pub use tuning::{KeyLimits, SolutionTuning};
+
+/// Ticks of the solution that are known ahead
+pub const SOLUTION_SIZE: usize = 1 << 10;
+
+/// Generates the keys of the solution, one tick at a time
+pub trait SolutionStrategy {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy>;
+
+    fn next(&mut self) -> Keys;
+
+    /// Writes the `strategy` line, with what changes as the keys are generated
+    fn save_state(&self, state: &mut StateWriter);
+
+    /// Reads the line written by [`SolutionStrategy::save_state`]
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String>;
+
+    /// Limits that the planner keeps the keys within, none by default
+    fn limits(&self) -> Option<KeyLimits> {
+        None
+    }
+}
+
+impl Clone for Box<dyn SolutionStrategy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Generated strategy, whose keys the planner keeps within the limits of its tuning
+#[derive(Clone)]
+struct Limited<S> {
+    strategy: S,
+    limits: KeyLimits,
+}
+
+impl<S: SolutionStrategy + Clone + 'static> SolutionStrategy for Limited<S> {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy> {
+        Box::new(self.clone())
+    }
+
+    fn next(&mut self) -> Keys {
+        self.strategy.next()
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        self.strategy.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.strategy.load_state(state)
+    }
+
+    fn limits(&self) -> Option<KeyLimits> {
+        Some(self.limits.clone())
+    }
+}
+
+/// Strategy chosen in the config, a recording that can not be loaded falls back to
+/// [`PhasedStrategy`]
+///
+/// The difficulty tunes the generated strategies, recordings are played as they are.
+pub fn strategy(
+    source: &SolutionSource,
+    seed: &Seed,
+    difficulty: Difficulty,
+) -> Box<dyn SolutionStrategy> {
+    let rng = seed.rng("solution");
+    let tuning = SolutionTuning::new(difficulty);
+
+    fn limited(
+        strategy: impl SolutionStrategy + Clone + 'static,
+        tuning: &SolutionTuning,
+    ) -> Box<dyn SolutionStrategy> {
+        Box::new(Limited {
+            strategy,
+            limits: KeyLimits::new(tuning),
+        })
+    }
+
+    match source {
+        SolutionSource::Phased => limited(PhasedStrategy::new(tuning.clone(), rng), &tuning),
+        SolutionSource::Markov => limited(MarkovStrategy::new(tuning.clone(), rng), &tuning),
+        SolutionSource::Rhythmic => limited(RhythmicStrategy::new(tuning.clone(), rng), &tuning),
+        SolutionSource::Recording(path) => match RecordingStrategy::load(path) {
+            Ok(recording) => Box::new(recording),
+            Err(error) => {
+                warning!("Can not load the solution {}: {error}", path.display());
+                limited(PhasedStrategy::new(tuning.clone(), rng), &tuning)
+            }
+        },
+    }
+}
+
+#[derive(Clone)]
+pub struct Solution {
+    first_index: usize,
+    generator: Box<dyn SolutionStrategy>,
+    /// Plans the newest keys
+    planner: Planner,
+    /// Planner as it was before the keys of the current tick, to plan them again
+    start: Planner,
+    keys: [Keys; SOLUTION_SIZE],
+    /// Times the solution has been planned, every key may have changed since
+    plan: usize,
+    ticks: usize,
+}
+
+/// Keys of the solution, from the current tick on
+impl Hash for Solution {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for time in 0..SOLUTION_SIZE {
+            self.keys(time).hash(state);
+        }
+    }
+}
+
+impl Solution {
+    /// Solution of the strategy, without planning until [`Solution::replan`] is called
+    pub fn new(mut generator: Box<dyn SolutionStrategy>) -> Solution {
+        let start = Planner::new(generator.limits());
+        let mut planner = start.clone();
+
+        Solution {
+            first_index: 0,
+            keys: std::array::from_fn(|_| planner.choose(generator.next())),
+            generator,
+            planner,
+            start,
+            plan: 0,
+            ticks: 0,
+        }
+    }
+
+    /// Plans the solution again for `games`, which must be in their current state
+    ///
+    /// The planned keys are kept where the games can survive them.
+    pub fn replan(&mut self, games: Vec<PlannedGame>) {
+        self.planner = self.start.with_games(games);
+
+        for time in 0..SOLUTION_SIZE {
+            let index = self.index(time);
+            self.keys[index] = self.planner.choose(self.keys[index]);
+        }
+        self.plan += 1;
+    }
+
+    pub fn plan(&self) -> usize {
+        self.plan
+    }
+
+    /// Ticks the solution has advanced
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    fn index(&self, time: usize) -> usize {
+        self.first_index.wrapping_add(time) & (SOLUTION_SIZE - 1)
+    }
+
+    pub fn keys(&self, time: usize) -> Keys {
+        if time >= SOLUTION_SIZE {
+            panic!("Index out of bounds");
+        }
+
+        self.keys[self.index(time)]
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state
+            .line("solution")
+            .value(self.first_index)
+            .value(self.plan)
+            .value(self.ticks);
+        state.line("solution-keys");
+        for keys in &self.keys {
+            state.value(keys);
+        }
+
+        self.generator.save_state(state);
+        self.start.save_state(state, "planner-start");
+        self.planner.save_state(state, "planner");
+    }
+
+    /// Reads the state written by [`Solution::save_state`], `games` must be in their current
+    /// state, like for [`Solution::replan`]
+    pub fn load_state(
+        &mut self,
+        state: &mut StateReader,
+        games: Vec<PlannedGame>,
+    ) -> Result<(), String> {
+        state.line("solution")?;
+        self.first_index = state.value()?;
+        if self.first_index >= SOLUTION_SIZE {
+            return Err(format!("invalid solution index {}", self.first_index));
+        }
+        self.plan = state.value()?;
+        self.ticks = state.value()?;
+
+        state.line("solution-keys")?;
+        for keys in &mut self.keys {
+            *keys = state.value()?;
+        }
+
+        self.generator.load_state(state)?;
+        self.start.load_state(state, "planner-start")?;
+        self.planner.load_state(state, "planner")?;
+
+        // The planned games are simulated again up to the newest keys
+        self.planner = self.planner.with_games(games);
+        for time in 0..SOLUTION_SIZE {
+            self.planner.advance(self.keys(time));
+        }
+        Ok(())
+    }
+
+    pub fn update(&mut self) {
+        // The keys were chosen within the limits, so the start keeps them as they are
+        self.start.choose(self.keys(0));
+        self.keys[self.first_index] = self.planner.choose(self.generator.next());
+        self.first_index = self.first_index.wrapping_add(1) & (SOLUTION_SIZE - 1);
+        self.ticks += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::input::Key;
+    use crate::run_config::RunConfig;
+
+    /// Ten minutes of solution
+    const TICKS: usize = 10 * 60 * 25;
+
+    struct Density {
+        /// Fraction of the ticks with some key pressed
+        pressing: f32,
+        presses_per_second: f32,
+        /// Most keys pressed at the same time
+        max_keys: usize,
+        /// Fewest ticks a key has been pressed
+        shortest_hold: usize,
+    }
+
+    fn density(source: SolutionSource, difficulty: Difficulty) -> Density {
+        let strategy = strategy(&source, &Seed::from_text("density"), difficulty);
+        let mut solution = Solution::new(strategy);
+        let mut pressing = 0;
+        let mut presses = 0;
+        let mut max_keys = 0;
+        let mut shortest_hold = usize::MAX;
+        let mut held = [0; 5];
+
+        for _ in 0..TICKS {
+            let keys = solution.keys(0);
+            solution.update();
+            pressing += keys.any_pressed() as usize;
+            presses += Key::ALL.iter().filter(|&&k| keys.just_pressed(k)).count();
+
+            let pressed = Key::ALL.iter().filter(|&&k| keys.pressing(k)).count();
+            max_keys = max_keys.max(pressed);
+
+            for (key, held) in Key::ALL.into_iter().zip(&mut held) {
+                if keys.pressing(key) {
+                    *held += 1;
+                } else if *held > 0 {
+                    shortest_hold = shortest_hold.min(*held);
+                    *held = 0;
+                }
+            }
+        }
+
+        Density {
+            pressing: pressing as f32 / TICKS as f32,
+            presses_per_second: presses as f32 * 25. / TICKS as f32,
+            max_keys,
+            shortest_hold,
+        }
+    }
+
+    #[test]
+    fn key_density() {
+        let phased = density(SolutionSource::Phased, Difficulty::Normal);
+        assert!((0.4..0.8).contains(&phased.pressing));
+        // The low frequency phase alone is below one press per second
+        assert!((1.0..3.).contains(&phased.presses_per_second));
+
+        let markov = density(SolutionSource::Markov, Difficulty::Normal);
+        assert!((0.3..0.6).contains(&markov.pressing));
+        assert!((1.5..2.5).contains(&markov.presses_per_second));
+
+        // Keys are pressed on beats, and only held for a short time
+        let rhythmic = density(SolutionSource::Rhythmic, Difficulty::Normal);
+        assert!((0.1..0.25).contains(&rhythmic.pressing));
+        assert!((0.6..1.3).contains(&rhythmic.presses_per_second));
+    }
+
+    #[test]
+    fn difficulty_tunes_the_keys() {
+        for source in SolutionSource::GENERATED {
+            let densities = Difficulty::ALL.map(|difficulty| {
+                let tuning = SolutionTuning::new(difficulty);
+                let density = density(source.clone(), difficulty);
+
+                assert!(
+                    density.max_keys <= tuning.max_keys,
+                    "{source:?} {difficulty:?}"
+                );
+                assert!(density.shortest_hold >= tuning.min_hold_ticks);
+                density.presses_per_second
+            });
+
+            let increasing = densities.windows(2).all(|pair| pair[0] < pair[1]);
+            assert!(increasing, "{source:?}: {densities:?}");
+        }
+    }
+
+    #[test]
+    fn recording_ends_without_keys() {
+        let mut keys = Keys::new();
+        keys.press(Key::Up);
+
+        let mut recording = RecordingStrategy::new([keys, Keys::new()]);
+        assert_eq!(recording.next(), keys);
+        assert_eq!(recording.next(), Keys::new());
+        assert_eq!(recording.next(), Keys::new());
+    }
+
+    #[test]
+    fn solution_file_round_trip() {
+        let path = std::env::temp_dir().join("miniterms-solution-round-trip.txt");
+        let mut generated = strategy(
+            &SolutionSource::Markov,
+            &Seed::from_text("export"),
+            Difficulty::Hard,
+        );
+        let keys: Vec<Keys> = (0..500).map(|_| generated.next()).collect();
+
+        let mut recorder = SolutionRecorder::create(&path, &RunConfig::new()).unwrap();
+        for (tick, &keys) in keys.iter().enumerate() {
+            recorder.record(tick, keys).unwrap();
+        }
+        // Ticks simulated again after a rewind were already written
+        let mut pressed = Keys::new();
+        pressed.press(Key::Space);
+        recorder.record(250, pressed).unwrap();
+        drop(recorder);
+
+        let mut loaded = RecordingStrategy::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        for keys in keys {
+            assert_eq!(loaded.next(), keys);
+        }
+        assert_eq!(loaded.next(), Keys::new());
+    }
+}
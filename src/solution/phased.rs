@@ -0,0 +1,138 @@
+use super::{SolutionStrategy, SolutionTuning};
+use crate::input::{Key, Keys};
+use crate::log::trace;
+use crate::save::{StateReader, StateWriter};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+/// Chance of choosing the low frequency phase, in percent
+const LOW_FREQ_CHANCE: u32 = 30;
+/// Duration of a phase in ticks
+const PHASE_TICKS: std::ops::Range<u32> = 50..100;
+
+/// A key is pressed or all the keys are released once every `LOW_FREQ_PERIOD` ticks on average
+const LOW_FREQ_PERIOD: f64 = 20.;
+
+/// Each tick, a random key is released with a chance of 1 in `HIGH_FREQ_RELEASE` and
+/// another is pressed with a chance of 1 in `HIGH_FREQ_PRESS`
+const HIGH_FREQ_RELEASE: f64 = 3.;
+const HIGH_FREQ_PRESS: f64 = 10.;
+
+/// Alternates between calm phases and phases with many key changes
+#[derive(Clone)]
+pub struct PhasedStrategy {
+    keys: Keys,
+    phase: Phase,
+    phase_time_left: usize,
+    tuning: SolutionTuning,
+    rng: ChaCha8Rng,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Phase {
+    LowFreq,
+    HighFreq,
+}
+
+impl Phase {
+    fn sample(rng: &mut ChaCha8Rng) -> Phase {
+        if rng.gen_range(0..100) < LOW_FREQ_CHANCE {
+            Phase::LowFreq
+        } else {
+            Phase::HighFreq
+        }
+    }
+}
+
+impl PhasedStrategy {
+    pub fn new(tuning: SolutionTuning, rng: ChaCha8Rng) -> Self {
+        Self {
+            keys: Keys::new(),
+            phase: Phase::LowFreq,
+            phase_time_left: 0,
+            tuning,
+            rng,
+        }
+    }
+
+    fn random_key(&mut self) -> Key {
+        self.tuning.random_key(&mut self.rng)
+    }
+
+    fn change(&mut self, period: f64) -> bool {
+        self.tuning.change(&mut self.rng, 1. / period)
+    }
+}
+
+impl SolutionStrategy for PhasedStrategy {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy> {
+        Box::new(self.clone())
+    }
+
+    fn next(&mut self) -> Keys {
+        if self.phase_time_left == 0 {
+            self.phase = Phase::sample(&mut self.rng);
+            self.phase_time_left = self.rng.gen_range(PHASE_TICKS) as usize;
+            trace!("{:?} phase for {} ticks", self.phase, self.phase_time_left);
+        } else {
+            self.phase_time_left -= 1;
+        }
+
+        self.keys.update();
+
+        match self.phase {
+            Phase::LowFreq => {
+                if self.change(LOW_FREQ_PERIOD) {
+                    if self.keys.any_pressed() {
+                        self.keys = Keys::new(); // Release all
+                    } else {
+                        let key = self.random_key();
+                        self.keys.press(key);
+                    }
+                }
+            }
+            Phase::HighFreq => {
+                if self.change(HIGH_FREQ_RELEASE) {
+                    let key = self.random_key();
+                    self.keys.release(key);
+                }
+
+                if self.change(HIGH_FREQ_PRESS) {
+                    let key = self.random_key();
+                    self.keys.press(key);
+                }
+            }
+        }
+
+        self.keys
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        let phase = match self.phase {
+            Phase::LowFreq => "low",
+            Phase::HighFreq => "high",
+        };
+        state
+            .line("strategy")
+            .value(self.keys)
+            .value(phase)
+            .value(self.phase_time_left)
+            .rng(&self.rng);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("strategy")?;
+        self.keys = state.value()?;
+        self.phase = match state.value::<String>()?.as_str() {
+            "low" => Phase::LowFreq,
+            "high" => Phase::HighFreq,
+            phase => return Err(format!("invalid phase '{phase}'")),
+        };
+        self.phase_time_left = state.value()?;
+        if self.phase_time_left >= PHASE_TICKS.end as usize {
+            return Err(format!("invalid phase time {}", self.phase_time_left));
+        }
+        self.rng = state.rng()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use super::KeyLimits;
+use crate::games::Plannable;
+use crate::input::{Key, Keys};
+use crate::log::debug;
+use crate::math::Size;
+use crate::run_config::Difficulty;
+use crate::save::{StateReader, StateWriter};
+use std::collections::HashMap;
+
+/// Ticks the planner looks ahead, more than a whole T-Rex jump
+const HORIZON: usize = 40;
+/// Game states simulated while checking a single key, before giving up
+const SEARCH_BUDGET: usize = 2000;
+
+/// State of a game at the end of the planned solution
+pub struct PlannedGame {
+    game: Box<dyn Plannable>,
+    size: Size<u16>,
+    difficulty: Difficulty,
+}
+
+impl PlannedGame {
+    pub fn new(game: &dyn Plannable, size: Size<u16>, difficulty: Difficulty) -> Self {
+        Self {
+            game: game.clone_box(),
+            size,
+            difficulty,
+        }
+    }
+
+    fn simulate(&mut self, keys: Keys) -> bool {
+        self.game.simulate(keys, self.size, self.difficulty)
+    }
+}
+
+impl Clone for PlannedGame {
+    fn clone(&self) -> Self {
+        Self {
+            game: self.game.clone_box(),
+            size: self.size,
+            difficulty: self.difficulty,
+        }
+    }
+}
+
+/// Chooses the keys of the solution so every game stays survivable
+///
+/// The games are simulated ahead of the real ones, up to the last planned tick. Each key
+/// proposed by the strategy is only accepted if all the games can survive `HORIZON` ticks
+/// after it, otherwise simpler keys are tried. When none of them survives, the one that
+/// survives the longest is chosen.
+///
+/// Every candidate goes through the key limits of the strategy, which follow the keys that
+/// are actually chosen.
+#[derive(Clone)]
+pub struct Planner {
+    games: Vec<PlannedGame>,
+    limits: Option<KeyLimits>,
+    /// Keys of the last planned tick
+    previous: Keys,
+}
+
+impl Planner {
+    /// Planner without games, that only enforces the limits
+    pub fn new(limits: Option<KeyLimits>) -> Self {
+        Self {
+            games: Vec::new(),
+            limits,
+            previous: Keys::new(),
+        }
+    }
+
+    /// Planner for `games` that continues from the keys chosen by this one
+    pub fn with_games(&self, games: Vec<PlannedGame>) -> Self {
+        Self {
+            games,
+            limits: self.limits.clone(),
+            previous: self.previous,
+        }
+    }
+
+    /// Returns the keys of the next tick and advances the games with them
+    pub fn choose(&mut self, proposal: Keys) -> Keys {
+        let wanted = if self.games.is_empty() {
+            proposal
+        } else {
+            self.search(proposal)
+        };
+
+        let keys = limit(&mut self.limits, wanted);
+        self.advance(keys);
+        self.previous = keys;
+
+        keys
+    }
+
+    /// Advances the games with keys that were chosen before
+    pub fn advance(&mut self, keys: Keys) {
+        for game in &mut self.games {
+            game.simulate(keys);
+        }
+    }
+
+    /// Writes the line `name` with the keys chosen last and the limits, but not the games
+    pub fn save_state(&self, state: &mut StateWriter, name: &str) {
+        state.line(name).value(self.previous);
+        if let Some(limits) = &self.limits {
+            limits.save_state(state);
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader, name: &str) -> Result<(), String> {
+        state.line(name)?;
+        self.previous = state.value()?;
+        if let Some(limits) = &mut self.limits {
+            limits.load_state(state)?;
+        }
+        Ok(())
+    }
+
+    /// Candidate that survives the longest, preferring the first ones
+    fn search(&self, proposal: Keys) -> Keys {
+        let mut held = self.previous;
+        held.update();
+
+        let candidates = [proposal, held, Keys::new(), tap(Key::Space), tap(Key::Down)];
+        let mut best = (0, proposal);
+
+        for keys in candidates {
+            let mut search = Search {
+                budget: SEARCH_BUDGET,
+                explored: HashMap::new(),
+            };
+            let ticks = search.survival(&self.games, &self.limits, keys, HORIZON);
+            if ticks > best.0 {
+                best = (ticks, keys);
+            }
+            if ticks > HORIZON {
+                return keys;
+            }
+        }
+
+        debug!(
+            "No survivable solution found, the best one dies in {} ticks",
+            best.0
+        );
+        best.1
+    }
+}
+
+fn tap(key: Key) -> Keys {
+    let mut keys = Keys::new();
+    keys.press(key);
+    keys
+}
+
+/// Keys allowed by `limits` when `wanted` are proposed
+fn limit(limits: &mut Option<KeyLimits>, wanted: Keys) -> Keys {
+    match limits {
+        Some(limits) => limits.apply(wanted),
+        None => wanted,
+    }
+}
+
+/// Depth, player states and key limits
+type SearchState = (usize, Vec<u64>, Option<KeyLimits>);
+
+struct Search {
+    budget: usize,
+    /// Ticks survived from the states already explored
+    explored: HashMap<SearchState, usize>,
+}
+
+impl Search {
+    /// Depth first search of keys that keep all the games alive for `depth` ticks after
+    /// `wanted`, returns the ticks they survive including the one of `wanted`
+    fn survival(
+        &mut self,
+        games: &[PlannedGame],
+        limits: &Option<KeyLimits>,
+        wanted: Keys,
+        depth: usize,
+    ) -> usize {
+        if self.budget == 0 {
+            return 0;
+        }
+        self.budget -= 1;
+
+        let mut limits = limits.clone();
+        let keys = limit(&mut limits, wanted);
+
+        let mut games = games.to_vec();
+        if !games.iter_mut().all(|game| game.simulate(keys)) {
+            return 0;
+        }
+
+        if depth == 0 {
+            return 1;
+        }
+
+        // The rest of the world does not depend on the keys
+        let players = games.iter().map(|g| g.game.player_state()).collect();
+        let state = (depth, players, limits.clone());
+        if let Some(&ticks) = self.explored.get(&state) {
+            return 1 + ticks;
+        }
+
+        let mut ticks = 0;
+        for next in [Keys::new(), tap(Key::Space), tap(Key::Down)] {
+            ticks = ticks.max(self.survival(&games, &limits, next, depth - 1));
+            if ticks == depth {
+                break;
+            }
+        }
+
+        self.explored.insert(state, ticks);
+        1 + ticks
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::games::trex::TRexGame;
+    use crate::seed::Seed;
+
+    #[test]
+    fn jumps_over_the_enemies() {
+        let trex = TRexGame::new(&Seed::from_text("planner"));
+        let game = PlannedGame::new(trex.world(), Size::new(156, 72), Difficulty::Normal);
+        let mut planner = Planner::new(None).with_games(vec![game.clone()]);
+
+        // Standing still runs into the first cactus, the planner has to jump
+        let mut followed = game;
+        let mut changed = 0;
+        for tick in 0..2000 {
+            let keys = planner.choose(Keys::new());
+            changed += (keys != Keys::new()) as usize;
+            assert!(followed.simulate(keys), "died at tick {tick}");
+        }
+        assert!(changed > 0);
+    }
+}
@@ -0,0 +1,115 @@
+use super::SolutionStrategy;
+use crate::input::Keys;
+use crate::replay::Replay;
+use crate::run_config::RunConfig;
+use crate::save::{StateReader, StateWriter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+const SOLUTION_HEADER: &str = "# miniterms solution v1";
+
+/// Plays back the keys of a replay or a solution file
+///
+/// Once the recording has finished no keys are pressed.
+#[derive(Clone)]
+pub struct RecordingStrategy {
+    /// Shared between the clones, so that snapshots of the solution are cheap
+    keys: Rc<[Keys]>,
+    next: usize,
+}
+
+impl RecordingStrategy {
+    pub fn new(keys: impl IntoIterator<Item = Keys>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            next: 0,
+        }
+    }
+
+    /// Loads the keys of a solution file, or of a replay otherwise
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines().enumerate();
+
+        match lines.next() {
+            Some((_, Ok(header))) if header == SOLUTION_HEADER => {}
+            Some((_, Err(error))) => return Err(error),
+            _ => return Ok(Self::new(Replay::load(path)?.into_keys())),
+        }
+
+        let mut keys = Vec::new();
+        for (number, line) in lines {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let tick_keys = line.parse().map_err(|error| {
+                let message = format!("{}:{}: {error}", path.display(), number + 1);
+                io::Error::new(io::ErrorKind::InvalidData, message)
+            })?;
+            keys.push(tick_keys);
+        }
+
+        Ok(Self::new(keys))
+    }
+}
+
+/// Writes the keys of the solution to a file while playing
+///
+/// File format: the header, the config of the run as `# key value` lines, then one line per
+/// tick with the [`Keys`] text representation. When loaded, empty lines and lines starting
+/// with `#` are skipped, so the files can also be written by hand.
+pub struct SolutionRecorder {
+    file: LineWriter<File>,
+    /// Tick of the next keys written, the ones before were written before a rewind
+    next_tick: usize,
+}
+
+impl SolutionRecorder {
+    pub fn create(path: &Path, config: &RunConfig) -> io::Result<SolutionRecorder> {
+        let mut file = LineWriter::new(File::create(path)?);
+
+        writeln!(file, "{SOLUTION_HEADER}")?;
+        for (key, value) in config.fields() {
+            writeln!(file, "# {key} {value}")?;
+        }
+
+        Ok(SolutionRecorder { file, next_tick: 0 })
+    }
+
+    /// Writes the keys of `tick`, unless they were already written
+    pub fn record(&mut self, tick: usize, keys: Keys) -> io::Result<()> {
+        if tick < self.next_tick {
+            return Ok(());
+        }
+
+        self.next_tick = tick + 1;
+        writeln!(self.file, "{keys}")
+    }
+}
+
+impl SolutionStrategy for RecordingStrategy {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy> {
+        Box::new(self.clone())
+    }
+
+    fn next(&mut self) -> Keys {
+        let keys = self.keys.get(self.next).copied().unwrap_or(Keys::new());
+        self.next += 1;
+        keys
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.line("strategy").value(self.next);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("strategy")?;
+        self.next = state.value()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use super::{SolutionStrategy, SolutionTuning};
+use crate::input::{Key, Keys};
+use crate::save::{StateReader, StateWriter};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+/// Ticks between two beats at the normal change rate, 0.6s
+const BEAT_TICKS: f64 = 15.;
+const BEATS_PER_BAR: usize = 4;
+/// Ticks a key is held on a beat, unless the tuning asks for longer holds
+const HOLD_TICKS: usize = 4;
+/// Chance of a beat being silent, in percent
+const REST_CHANCE: u32 = 40;
+/// Chance of repeating the previous bar, in percent
+const REPEAT_CHANCE: u32 = 50;
+
+/// Presses keys to a beat, in bars that are often repeated
+#[derive(Clone)]
+pub struct RhythmicStrategy {
+    bar: [Option<Key>; BEATS_PER_BAR],
+    tick: usize,
+    beat_ticks: usize,
+    hold_ticks: usize,
+    keys: Keys,
+    tuning: SolutionTuning,
+    rng: ChaCha8Rng,
+}
+
+impl RhythmicStrategy {
+    pub fn new(tuning: SolutionTuning, rng: ChaCha8Rng) -> Self {
+        let beat_ticks = (BEAT_TICKS / tuning.change_rate).round() as usize;
+        let hold_ticks = HOLD_TICKS.max(tuning.min_hold_ticks);
+
+        let mut strategy = Self {
+            bar: [None; BEATS_PER_BAR],
+            tick: 0,
+            beat_ticks: beat_ticks.max(hold_ticks + 1),
+            hold_ticks,
+            keys: Keys::new(),
+            tuning,
+            rng,
+        };
+        strategy.new_bar();
+        strategy
+    }
+
+    fn new_bar(&mut self) {
+        for beat in &mut self.bar {
+            *beat = if self.rng.gen_range(0..100) < REST_CHANCE {
+                None
+            } else {
+                Some(self.tuning.random_key(&mut self.rng))
+            };
+        }
+    }
+}
+
+impl SolutionStrategy for RhythmicStrategy {
+    fn clone_box(&self) -> Box<dyn SolutionStrategy> {
+        Box::new(self.clone())
+    }
+
+    fn next(&mut self) -> Keys {
+        let bar_ticks = self.beat_ticks * BEATS_PER_BAR;
+        if self.tick == bar_ticks {
+            self.tick = 0;
+            if self.rng.gen_range(0..100) >= REPEAT_CHANCE {
+                self.new_bar();
+            }
+        }
+
+        let beat = self.bar[self.tick / self.beat_ticks];
+        let beat_tick = self.tick % self.beat_ticks;
+        self.tick += 1;
+
+        self.keys.update();
+        if let Some(key) = beat {
+            if beat_tick == 0 {
+                self.keys.press(key);
+            } else if beat_tick == self.hold_ticks {
+                self.keys.release(key);
+            }
+        }
+
+        self.keys
+    }
+
+    /// The beats of the bar are written as the number of their key, or `-` for a rest
+    fn save_state(&self, state: &mut StateWriter) {
+        state.line("strategy");
+        for beat in self.bar {
+            state.value(beat.map_or("-".to_string(), |key| (key as u8).to_string()));
+        }
+        state.value(self.tick).value(self.keys).rng(&self.rng);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        state.line("strategy")?;
+        for beat in &mut self.bar {
+            *beat = match state.value::<String>()?.as_str() {
+                "-" => None,
+                key => match key.parse() {
+                    Ok(n) if (n as usize) < Key::ALL.len() => Some(Key::from_u8(n)),
+                    _ => return Err(format!("invalid beat '{key}'")),
+                },
+            };
+        }
+        self.tick = state.value()?;
+        // The tick reaches the length of the bar before the next one starts
+        if self.tick > self.beat_ticks * BEATS_PER_BAR {
+            return Err(format!("invalid beat tick {}", self.tick));
+        }
+        self.keys = state.value()?;
+        self.rng = state.rng()?;
+        Ok(())
+    }
+}
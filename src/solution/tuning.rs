@@ -0,0 +1,142 @@
+use crate::input::{Key, Keys};
+use crate::run_config::Difficulty;
+use crate::save::{StateReader, StateWriter};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+/// Parameters of the generated solution, chosen by the difficulty
+#[derive(Clone, Debug)]
+pub struct SolutionTuning {
+    /// Multiplies how often the strategies change the keys
+    pub change_rate: f64,
+    /// Keys pressed at the same time
+    pub max_keys: usize,
+    /// Ticks a key stays pressed before it can be released
+    pub min_hold_ticks: usize,
+    /// Relative chance of choosing each key of [`Key::ALL`]
+    pub key_weights: [u32; 5],
+}
+
+impl SolutionTuning {
+    pub fn new(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => Self {
+                change_rate: 0.6,
+                max_keys: 1,
+                min_hold_ticks: 6,
+                key_weights: [10, 10, 10, 10, 60],
+            },
+            Difficulty::Normal => Self {
+                change_rate: 1.,
+                max_keys: 2,
+                min_hold_ticks: 3,
+                key_weights: [15, 15, 15, 15, 40],
+            },
+            Difficulty::Hard => Self {
+                change_rate: 1.5,
+                max_keys: 3,
+                min_hold_ticks: 1,
+                key_weights: [20, 20, 20, 20, 20],
+            },
+        }
+    }
+
+    /// Returns true with a chance of `chance` scaled by the change rate
+    pub fn change(&self, rng: &mut ChaCha8Rng, chance: f64) -> bool {
+        rng.gen_bool((chance * self.change_rate).clamp(0., 1.))
+    }
+
+    /// Key chosen using the key weights
+    pub fn random_key(&self, rng: &mut ChaCha8Rng) -> Key {
+        let total: u32 = self.key_weights.iter().sum();
+        let mut choice = rng.gen_range(0..total);
+
+        for (key, &weight) in Key::ALL.into_iter().zip(&self.key_weights) {
+            if choice < weight {
+                return key;
+            }
+            choice -= weight;
+        }
+
+        Key::Space
+    }
+
+    /// Weight of `key` relative to the average weight
+    pub fn key_weight(&self, key: Key) -> f64 {
+        let total: u32 = self.key_weights.iter().sum();
+        let weight = self.key_weights[key as usize];
+        weight as f64 * Key::ALL.len() as f64 / total as f64
+    }
+}
+
+/// Enforces the key limits of a [`SolutionTuning`] on the output of a strategy
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct KeyLimits {
+    max_keys: usize,
+    min_hold_ticks: usize,
+    /// Ticks each key of [`Key::ALL`] has been pressed, up to `min_hold_ticks`
+    held: [usize; 5],
+    keys: Keys,
+}
+
+impl KeyLimits {
+    pub fn new(tuning: &SolutionTuning) -> Self {
+        Self {
+            max_keys: tuning.max_keys,
+            min_hold_ticks: tuning.min_hold_ticks,
+            held: [0; 5],
+            keys: Keys::new(),
+        }
+    }
+
+    pub fn apply(&mut self, wanted: Keys) -> Keys {
+        self.keys.update();
+
+        for (key, held) in Key::ALL.into_iter().zip(&mut self.held) {
+            if self.keys.pressing(key) && !wanted.pressing(key) && *held >= self.min_hold_ticks {
+                self.keys.release(key);
+            }
+        }
+
+        for key in Key::ALL {
+            let pressed = Key::ALL.iter().filter(|&&k| self.keys.pressing(k)).count();
+            if wanted.just_pressed(key) && !self.keys.pressing(key) && pressed < self.max_keys {
+                self.keys.press(key);
+                self.held[key as usize] = 0;
+            }
+        }
+
+        // Stops counting once the key can be released, so equal limits compare equal
+        for (key, held) in Key::ALL.into_iter().zip(&mut self.held) {
+            if self.keys.pressing(key) {
+                *held = (*held + 1).min(self.min_hold_ticks);
+            }
+        }
+
+        self.keys
+    }
+
+    /// Adds the keys and how long they have been held to the line
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.value(self.keys);
+        for held in self.held {
+            state.value(held);
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), String> {
+        self.keys = state.value()?;
+        let pressed = Key::ALL.iter().filter(|&&k| self.keys.pressing(k)).count();
+        if pressed > self.max_keys {
+            return Err(format!("more than {} keys pressed", self.max_keys));
+        }
+
+        for held in &mut self.held {
+            *held = state.value()?;
+            if *held > self.min_hold_ticks {
+                return Err(format!("invalid held ticks {held}"));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    io::{self, stdout, Stdout},
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::log::{Level, LOG};
+use crossterm::{
+    cursor::Show,
+    event::{DisableFocusChange, EnableFocusChange},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{backend::CrosstermBackend, terminal::Terminal};
+
+/// Amount of log records printed after a panic
+const PANIC_LOG_RECORDS: usize = 20;
+
+pub type Term = Terminal<CrosstermBackend<Stdout>>;
+
+/// Flags raised by the signal handlers, they are checked every tick by the main loop.
+pub struct Signals {
+    terminate: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+    resumed: Arc<AtomicBool>,
+}
+
+pub fn init_terminal() -> io::Result<Term> {
+    enter_terminal()?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
+
+fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    stdout()
+        .execute(EnterAlternateScreen)?
+        .execute(EnableFocusChange)?;
+    Ok(())
+}
+
+pub fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    stdout()
+        .execute(DisableFocusChange)?
+        .execute(LeaveAlternateScreen)?
+        .execute(Show)?;
+    Ok(())
+}
+
+/// Restores the terminal before printing the panic message, followed by the latest log records.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+
+        // The panic may have happened while the log was locked
+        let mut log = match LOG.try_lock() {
+            Ok(log) => log,
+            Err(std::sync::TryLockError::Poisoned(poison)) => poison.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => return,
+        };
+
+        log.push(Level::Error, module_path!(), info.to_string());
+
+        eprintln!("\nRecent log:");
+        let skip = log.records().count().saturating_sub(PANIC_LOG_RECORDS);
+        for record in log.records().skip(skip) {
+            eprintln!("{record}");
+        }
+    }));
+}
+
+impl Signals {
+    pub fn register() -> io::Result<Signals> {
+        use signal_hook::{consts::*, flag::register};
+
+        let signals = Signals {
+            terminate: Arc::new(AtomicBool::new(false)),
+            suspend: Arc::new(AtomicBool::new(false)),
+            resumed: Arc::new(AtomicBool::new(false)),
+        };
+
+        register(SIGINT, signals.terminate.clone())?;
+        register(SIGTERM, signals.terminate.clone())?;
+
+        #[cfg(unix)]
+        {
+            register(SIGTSTP, signals.suspend.clone())?;
+            register(SIGCONT, signals.resumed.clone())?;
+        }
+
+        Ok(signals)
+    }
+
+    pub fn terminate_requested(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    /// Handles the pending SIGTSTP and SIGCONT signals, returns true if the process has been resumed
+    pub fn handle_pending(&self, terminal: &mut Term) -> io::Result<bool> {
+        if self.suspend.swap(false, Ordering::Relaxed) {
+            suspend(terminal)?;
+            self.resumed.store(false, Ordering::Relaxed);
+            return Ok(true);
+        }
+
+        // Stopped by a signal that can not be caught (SIGSTOP), the terminal may have been reset
+        if self.resumed.swap(false, Ordering::Relaxed) {
+            enter_terminal()?;
+            terminal.clear()?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+}
+
+/// Gives the terminal back to the shell and stops the process until it receives a SIGCONT.
+pub fn suspend(terminal: &mut Term) -> io::Result<()> {
+    restore_terminal()?;
+
+    #[cfg(unix)]
+    signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+
+    enter_terminal()?;
+    terminal.clear()
+}
@@ -0,0 +1,247 @@
+use crate::daily::{daily_config, Date};
+use crate::games::GameKind;
+use crate::log::warning;
+use crate::menu::centered;
+use crate::run_code;
+use crate::run_config::RunConfig;
+use crate::scores::HighScores;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/// Amount of high scores listed in the title screen
+const SHOWN_SCORES: usize = 5;
+
+pub enum TitleAction {
+    Start(RunConfig),
+    /// Resume the saved run
+    Resume,
+    Quit,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Item {
+    Game(GameKind),
+    Mode,
+    Difficulty,
+    Solution,
+    Seed,
+    /// Run code typed by the player, shows the code of the current config otherwise
+    Code,
+    Start,
+    Resume,
+    Daily,
+    Quit,
+}
+
+pub struct TitleScreen {
+    config: RunConfig,
+    selected: usize,
+    code_input: String,
+    invalid_code: bool,
+    resume_failed: bool,
+}
+
+impl TitleScreen {
+    pub fn new(config: RunConfig) -> Self {
+        let start = Self::items().position(|item| item == Item::Start);
+
+        Self {
+            config,
+            selected: start.unwrap_or(0),
+            code_input: String::new(),
+            invalid_code: false,
+            resume_failed: false,
+        }
+    }
+
+    /// Shows that the saved run could not be resumed
+    pub fn resume_failed(&mut self) {
+        self.resume_failed = true;
+    }
+
+    fn items() -> impl Iterator<Item = Item> {
+        let games = GameKind::ALL.into_iter().map(Item::Game);
+        games.chain([
+            Item::Mode,
+            Item::Difficulty,
+            Item::Solution,
+            Item::Seed,
+            Item::Code,
+            Item::Start,
+            Item::Resume,
+            Item::Daily,
+            Item::Quit,
+        ])
+    }
+
+    fn selected_item(&self) -> Item {
+        Self::items().nth(self.selected).unwrap_or(Item::Start)
+    }
+
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> Option<TitleAction> {
+        if key_event.kind == KeyEventKind::Release {
+            return None;
+        }
+
+        let items = Self::items().count();
+        let item = self.selected_item();
+
+        match (item, key_event.code) {
+            (Item::Seed, KeyCode::Char(c)) if !c.is_whitespace() => self.config.seed.push(c),
+            (Item::Seed, KeyCode::Backspace) => {
+                self.config.seed.pop();
+            }
+            (Item::Code, KeyCode::Char(c)) if c.is_ascii_alphanumeric() || c == '-' => {
+                self.code_input.push(c);
+                self.invalid_code = false;
+            }
+            (Item::Code, KeyCode::Backspace) => {
+                self.code_input.pop();
+                self.invalid_code = false;
+            }
+            (Item::Code, KeyCode::Enter) if !self.code_input.is_empty() => {
+                match run_code::decode(&self.code_input) {
+                    Ok(config) => {
+                        self.config = config;
+                        self.code_input.clear();
+                    }
+                    Err(error) => {
+                        warning!("{error}");
+                        self.invalid_code = true;
+                    }
+                }
+            }
+
+            (_, KeyCode::Up | KeyCode::Char('k')) => {
+                self.selected = (self.selected + items - 1) % items;
+                self.code_input.clear();
+            }
+            (_, KeyCode::Down | KeyCode::Char('j')) => {
+                self.selected = (self.selected + 1) % items;
+                self.code_input.clear();
+            }
+            (_, KeyCode::Esc | KeyCode::Char('q')) => return Some(TitleAction::Quit),
+
+            (
+                Item::Mode,
+                KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l'),
+            ) => {
+                self.config.mode = self.config.mode.toggle();
+            }
+            (Item::Difficulty, KeyCode::Left | KeyCode::Char('h')) => {
+                self.config.difficulty = self.config.difficulty.previous();
+            }
+            (Item::Difficulty, KeyCode::Right | KeyCode::Char('l')) => {
+                self.config.difficulty = self.config.difficulty.next();
+            }
+            (
+                Item::Solution,
+                KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l'),
+            ) => {
+                self.config.solution = self.config.solution.next();
+            }
+            (Item::Game(game), KeyCode::Enter | KeyCode::Char(' ')) => {
+                self.config.toggle_game(game);
+            }
+            (Item::Start, KeyCode::Enter | KeyCode::Char(' ')) if !self.config.games.is_empty() => {
+                return Some(TitleAction::Start(self.config.clone()));
+            }
+            (Item::Resume, KeyCode::Enter | KeyCode::Char(' ')) => {
+                return Some(TitleAction::Resume);
+            }
+            (Item::Daily, KeyCode::Enter | KeyCode::Char(' ')) => {
+                return Some(TitleAction::Start(daily_config(Date::today())));
+            }
+            (Item::Quit, KeyCode::Enter | KeyCode::Char(' ')) => return Some(TitleAction::Quit),
+            _ => {}
+        }
+
+        None
+    }
+
+    pub fn render(&self, frame: &mut Frame, scores: &HighScores) {
+        use Constraint::*;
+
+        let menu: Vec<Line> = Self::items()
+            .enumerate()
+            .map(|(i, item)| self.item_line(item, i == self.selected))
+            .collect();
+
+        let mut score_lines = vec![Line::from("High Scores").bold(), Line::default()];
+        score_lines.extend(scores.scores().iter().take(SHOWN_SCORES).enumerate().map(
+            |(i, score)| {
+                let games: Vec<_> = score.games.iter().map(|game| game.name()).collect();
+                Line::from(format!(
+                    "{}. {:>6}  {:<6}  {}",
+                    i + 1,
+                    score.points,
+                    score.difficulty.name(),
+                    games.join(", "),
+                ))
+            },
+        ));
+        if scores.scores().is_empty() {
+            score_lines.push(Line::from("No scores yet").dark_gray());
+        }
+
+        let height = menu.len().max(score_lines.len()) as u16 + 2;
+        let area = centered(frame.size(), 80, height + 3);
+        let [title_area, body] = Layout::vertical([Length(3), Fill(1)]).areas(area);
+        let [menu_area, scores_area] = Layout::horizontal([Length(38), Fill(1)]).areas(body);
+
+        frame.render_widget(
+            Paragraph::new("M I N I T E R M S")
+                .bold()
+                .alignment(Alignment::Center)
+                .block(Block::bordered()),
+            title_area,
+        );
+        frame.render_widget(
+            Paragraph::new(Text::from(menu)).block(Block::bordered().title("New Run")),
+            menu_area,
+        );
+        frame.render_widget(
+            Paragraph::new(Text::from(score_lines)).block(Block::bordered()),
+            scores_area,
+        );
+    }
+
+    fn item_line(&self, item: Item, selected: bool) -> Line<'static> {
+        let text = match item {
+            Item::Game(game) => {
+                let check = if self.config.has_game(game) { 'x' } else { ' ' };
+                format!("[{check}] {}", game.name())
+            }
+            Item::Mode => format!("Mode:       < {} >", self.config.mode.name()),
+            Item::Difficulty => format!("Difficulty: < {} >", self.config.difficulty.name()),
+            Item::Solution => format!("Solution:   < {} >", self.config.solution.name()),
+            Item::Seed if selected => format!("Seed: {}_", self.config.seed),
+            Item::Seed if self.config.seed.is_empty() => "Seed: (default)".to_string(),
+            Item::Seed => format!("Seed: {}", self.config.seed),
+            Item::Code if self.code_input.is_empty() => {
+                let code = run_code::encode(&self.config);
+                format!("Code: {}", code.as_deref().unwrap_or("(local recording)"))
+            }
+            Item::Code if self.invalid_code => format!("Code: (invalid) {}_", self.code_input),
+            Item::Code => format!("Code: {}_", self.code_input),
+            Item::Start if self.config.games.is_empty() => "Start (choose a game)".to_string(),
+            Item::Start => "Start".to_string(),
+            Item::Resume if self.resume_failed => "Resume saved run (failed)".to_string(),
+            Item::Resume => "Resume saved run".to_string(),
+            Item::Daily => format!("Daily challenge {}", Date::today()),
+            Item::Quit => "Quit".to_string(),
+        };
+
+        if selected {
+            Line::styled(format!("> {text}"), Style::new().yellow())
+        } else {
+            Line::from(format!("  {text}"))
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::games::{trex::TRexGame, GameContext, GameKind};
+use crate::input::Keys;
+use crate::math::Size;
+use crate::run_config::{Difficulty, RunConfig, SolutionSource};
+use crate::seed::Seed;
+use crate::solution::{self, PlannedGame, Solution};
+
+/// Pixels of the games while verifying, the T-Rex of a 80x20 panel
+const GAME_SIZE: Size<u16> = Size {
+    width: 156,
+    height: 72,
+};
+
+pub struct VerifyOptions {
+    pub games: Vec<GameKind>,
+    pub difficulties: Vec<Difficulty>,
+    pub solution: SolutionSource,
+    pub seeds: usize,
+    pub ticks: usize,
+}
+
+/// Result of following the solution in a single game
+pub struct Verdict {
+    pub game: GameKind,
+    pub config: RunConfig,
+    /// Ticks where the player died, `None` if the game can not be simulated yet
+    pub deaths: Option<Vec<usize>>,
+}
+
+impl VerifyOptions {
+    pub fn new() -> Self {
+        Self {
+            games: GameKind::ALL.to_vec(),
+            difficulties: Difficulty::ALL.to_vec(),
+            solution: SolutionSource::Phased,
+            seeds: 10,
+            ticks: 10_000,
+        }
+    }
+
+    /// Every game, difficulty and seed combination, the seeds are `verify-0`, `verify-1`...
+    pub fn configs(&self) -> impl Iterator<Item = (GameKind, RunConfig)> + '_ {
+        self.games.iter().flat_map(move |&game| {
+            self.difficulties.iter().flat_map(move |&difficulty| {
+                (0..self.seeds).map(move |seed| {
+                    let config = RunConfig {
+                        games: vec![game],
+                        difficulty,
+                        seed: format!("verify-{seed}"),
+                        solution: self.solution.clone(),
+                        ..RunConfig::new()
+                    };
+                    (game, config)
+                })
+            })
+        })
+    }
+}
+
+/// Follows the solution of `config` in `game` for `ticks`
+pub fn verify(game: GameKind, config: RunConfig, ticks: usize) -> Verdict {
+    let seed = Seed::from_text(&config.seed);
+    let strategy = solution::strategy(&config.solution, &seed, config.difficulty);
+    let solution = Solution::new(strategy);
+
+    Verdict {
+        game,
+        deaths: follow(game, &seed, config.difficulty, solution, ticks, |keys| keys),
+        config,
+    }
+}
+
+/// Plays `game` for `ticks` using the keys of the solution as the only input, `player` turns
+/// them into the keys of the player
+///
+/// Returns the ticks where the player died, or `None` if the game can not be simulated yet.
+fn follow(
+    game: GameKind,
+    seed: &Seed,
+    difficulty: Difficulty,
+    mut solution: Solution,
+    ticks: usize,
+    player: impl Fn(Keys) -> Keys,
+) -> Option<Vec<usize>> {
+    match game {
+        GameKind::TRex => {
+            let mut trex = TRexGame::new(seed);
+            let mut deaths = Vec::new();
+            solution.replan(vec![PlannedGame::new(trex.world(), GAME_SIZE, difficulty)]);
+
+            for tick in 0..ticks {
+                let was_colliding = trex.is_colliding();
+
+                trex.update(&mut GameContext {
+                    size: GAME_SIZE,
+                    keys: player(solution.keys(0)),
+                    solution: &solution,
+                    difficulty,
+                });
+                solution.update();
+
+                if trex.is_colliding() && !was_colliding {
+                    deaths.push(tick);
+                }
+            }
+
+            Some(deaths)
+        }
+        _ => None,
+    }
+}
+
+/// Verifies every combination of the options and prints a line for each one
+///
+/// Returns true if the solution survives all of them.
+pub fn run(options: &VerifyOptions) -> bool {
+    let mut survived = true;
+
+    for (game, config) in options.configs() {
+        let verdict = verify(game, config, options.ticks);
+        let name = format!(
+            "{:<18} {:<6} {:<10}",
+            verdict.game.name(),
+            verdict.config.difficulty.name(),
+            verdict.config.seed
+        );
+
+        match &verdict.deaths {
+            None => println!("{name} skipped, the game is not simulated yet"),
+            Some(deaths) if deaths.is_empty() => println!("{name} ok"),
+            Some(deaths) => {
+                survived = false;
+                println!(
+                    "{name} died {} times, first at tick {}",
+                    deaths.len(),
+                    deaths[0]
+                );
+            }
+        }
+    }
+
+    survived
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_deaths() {
+        let seed = Seed::from_text("");
+        let difficulty = Difficulty::Normal;
+        let solution = || {
+            let strategy = solution::strategy(&SolutionSource::Phased, &seed, difficulty);
+            Solution::new(strategy)
+        };
+
+        // The enemies are dodged by the solution, a player that never jumps runs into them
+        let deaths = follow(GameKind::TRex, &seed, difficulty, solution(), 2000, |_| {
+            Keys::new()
+        });
+        assert!(!deaths.unwrap().is_empty());
+
+        let deaths = follow(GameKind::Tetris, &seed, difficulty, solution(), 10, |k| k);
+        assert!(deaths.is_none());
+    }
+
+    /// Fewer seeds and ticks than `miniterms verify`, which checks the rest
+    #[test]
+    fn solution_is_survivable() {
+        let options = VerifyOptions {
+            seeds: 2,
+            ticks: 5000,
+            ..VerifyOptions::new()
+        };
+        for (game, config) in options.configs() {
+            let verdict = verify(game, config, options.ticks);
+            let deaths = verdict.deaths.unwrap_or_default();
+            assert!(
+                deaths.is_empty(),
+                "{game:?} {:?}: {deaths:?}",
+                verdict.config
+            );
+        }
+    }
+}